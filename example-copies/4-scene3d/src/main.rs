@@ -1,23 +1,39 @@
-use core::isometric_camera::CameraManager;
-use std::{f32::consts::PI, time::Duration};
+use std::f32::consts::PI;
 
-use bevy::core::FrameCount;
 use bevy::{picking::pointer::PointerInteraction, prelude::*};
 
+use core::character_animation::CharacterAnimator;
+use core::collectibles::{Collectible, Collector};
+use core::health::{
+    hazards::{FallDamage, Hazard},
+    DamageType, Health,
+};
 use core::input_manager::{button, motion, Action, InputManager, InputModeChanged};
+use core::isometric_camera::{follow::CameraFollow, CameraManager, CameraMode};
+use core::player_controller::{glide::Updraft, ControllerCollisionIgnore, PlayerController};
+use core::respawn::{Checkpoint, RespawnSettings, Respawnable};
+use core::skills::{
+    effects::{Bloomable, Rottable},
+    SkillCaster,
+};
+use core::temporary_skills::{bombs::Explodable, TemporarySkillPickup, TemporarySkills};
+use core::tollgates::{Tollgate, TollgateObstacle, UnlockCondition};
+use core::volume::BoxVolume;
 
 const BOXY_PATH: &str = "models/boxy.glb";
+const BOXY_TUNING_PATH: &str = "players/boxy.controller.ron";
+const BOXY_ANIMATIONS_PATH: &str = "players/boxy.anim.ron";
+const BOXY_SKILLS_PATH: &str = "players/boxy.skills.ron";
+const BOXY_TEMPORARY_SKILLS_PATH: &str = "players/boxy.temporary.ron";
+const REVEAL_RAIL_PATH: &str = "cameras/reveal.rail.ron";
 
 fn main() {
     App::new()
         .add_plugins((DefaultPlugins, core::CorePlugin, MeshPickingPlugin))
         .insert_resource(GroundEntity::default())
         .add_systems(Startup, (setup, register_input))
-        .add_systems(Update, (setup_once_loaded, read_input))
-        .add_systems(
-            Update,
-            (draw_cursor, rotate_boxy, keyboard_animation_control),
-        )
+        .add_systems(Update, read_input)
+        .add_systems(Update, (draw_cursor, rotate_boxy))
         .add_observer(get_input_mode_change_trigger)
         .run();
 }
@@ -25,25 +41,69 @@ fn main() {
 static ACTIVATE: Action = Action("activate");
 static SPAWN_SHROOM: Action = Action("spawn_shroom");
 static MOVEMENT: Action = Action("movement");
-static CAMERA: Action = Action("camera");
+static JUMP: Action = Action("jump");
+static SKILL_1: Action = Action("skill_1");
+static SKILL_2: Action = Action("skill_2");
+static USE_ITEM: Action = Action("use_item");
+static CYCLE_ITEM: Action = Action("cycle_item");
 
 fn register_input(mut im: ResMut<InputManager>) {
     im.register_action_button(
         ACTIVATE,
         vec![
             button::Variant::Keyboard(KeyCode::KeyE),
-            button::Variant::Gamepad(GamepadButton::South),
+            button::Variant::Gamepad(GamepadButton::West),
         ],
     );
 
     im.register_action_button(
         SPAWN_SHROOM,
         vec![
-            button::Variant::Keyboard(KeyCode::Space),
+            button::Variant::Keyboard(KeyCode::KeyR),
             button::Variant::Gamepad(GamepadButton::North),
         ],
     );
 
+    im.register_action_button(
+        JUMP,
+        vec![
+            button::Variant::Keyboard(KeyCode::Space),
+            button::Variant::Gamepad(GamepadButton::South),
+        ],
+    );
+
+    im.register_action_button(
+        SKILL_1,
+        vec![
+            button::Variant::Keyboard(KeyCode::KeyZ),
+            button::Variant::Gamepad(GamepadButton::LeftTrigger2),
+        ],
+    );
+
+    im.register_action_button(
+        SKILL_2,
+        vec![
+            button::Variant::Keyboard(KeyCode::KeyX),
+            button::Variant::Gamepad(GamepadButton::RightTrigger2),
+        ],
+    );
+
+    im.register_action_button(
+        USE_ITEM,
+        vec![
+            button::Variant::Keyboard(KeyCode::KeyC),
+            button::Variant::Gamepad(GamepadButton::East),
+        ],
+    );
+
+    im.register_action_button(
+        CYCLE_ITEM,
+        vec![
+            button::Variant::Keyboard(KeyCode::KeyQ),
+            button::Variant::Gamepad(GamepadButton::DPadRight),
+        ],
+    );
+
     im.register_action_motion(
         MOVEMENT,
         vec![
@@ -65,32 +125,6 @@ fn register_input(mut im: ResMut<InputManager>) {
             },
         ],
     );
-
-    im.register_action_motion(
-        CAMERA,
-        vec![
-            motion::Entry {
-                input_type: core::input_manager::InputType::Keyboard,
-                relations: vec![
-                    motion::Relation::KeyCode(KeyCode::KeyK, motion::Axis::PosY),
-                    motion::Relation::KeyCode(KeyCode::KeyJ, motion::Axis::NegY),
-                    motion::Relation::KeyCode(KeyCode::KeyL, motion::Axis::PosX),
-                    motion::Relation::KeyCode(KeyCode::KeyH, motion::Axis::NegX),
-                ],
-            },
-            motion::Entry {
-                input_type: core::input_manager::InputType::Mouse,
-                relations: vec![motion::Relation::Mouse(20.)],
-            },
-            motion::Entry {
-                input_type: core::input_manager::InputType::Gamepad,
-                relations: vec![
-                    motion::Relation::GamepadAxis(GamepadAxis::RightStickY, motion::Axis::Y),
-                    motion::Relation::GamepadAxis(GamepadAxis::RightStickX, motion::Axis::X),
-                ],
-            },
-        ],
-    );
 }
 
 fn get_input_mode_change_trigger(trigger: Trigger<InputModeChanged>) {
@@ -98,17 +132,10 @@ fn get_input_mode_change_trigger(trigger: Trigger<InputModeChanged>) {
     println!("TRIGGER input_mode_change: {:?}", event);
 }
 
-fn read_input(im: Res<InputManager>, fc: Res<FrameCount>, mut camera: ResMut<CameraManager>) {
+fn read_input(im: Res<InputManager>) {
     if im.is_action_just_pressed(ACTIVATE) {
         println!(" !!! ACTIVATE !!! ")
     }
-
-    // camera.move_camera_global(im.get_motion3z(MOVEMENT));
-    camera.move_camera_local(im.get_motion3z(MOVEMENT));
-
-    let motion = im.get_motion(CAMERA);
-    camera.rotate_camera_yaw(motion.x);
-    camera.rotate_camera_pitch(motion.y);
 }
 
 #[derive(Component)]
@@ -127,40 +154,51 @@ impl GroundEntity {
     }
 }
 
-#[derive(Resource)]
-struct Animations {
-    animations: Vec<AnimationNodeIndex>,
-    graph: Handle<AnimationGraph>,
-}
-
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut ground_entity: ResMut<GroundEntity>,
     asset_server: Res<AssetServer>,
-    mut graphs: ResMut<Assets<AnimationGraph>>,
+    mut camera_manager: ResMut<CameraManager>,
+    mut respawn_settings: ResMut<RespawnSettings>,
 ) {
     // boxy
-    let (graph, node_indices) = AnimationGraph::from_clips([
-        asset_server.load(GltfAssetLabel::Animation(0).from_asset(BOXY_PATH)),
-        asset_server.load(GltfAssetLabel::Animation(1).from_asset(BOXY_PATH)),
-    ]);
-    let graph_handle = graphs.add(graph);
-    commands.insert_resource(Animations {
-        animations: node_indices,
-        graph: graph_handle,
-    });
     let boxy_transform: Transform = {
         let mut transform = Transform::from_xyz(0.0, 1.0, 0.0);
         transform.rotate_y(PI * 1.5);
         transform
     };
-    commands.spawn((
-        SceneRoot(asset_server.load(GltfAssetLabel::Scene(0).from_asset(BOXY_PATH))),
-        boxy_transform,
-        Boxy,
-    ));
+    let boxy = commands
+        .spawn((
+            SceneRoot(asset_server.load(GltfAssetLabel::Scene(0).from_asset(BOXY_PATH))),
+            boxy_transform,
+            Boxy,
+            PlayerController::new(MOVEMENT)
+                .with_jump(JUMP)
+                .with_glide(JUMP)
+                .with_tuning(asset_server.load(BOXY_TUNING_PATH)),
+            CharacterAnimator::new(
+                asset_server.load(BOXY_ANIMATIONS_PATH),
+                asset_server.load(BOXY_PATH),
+            ),
+            {
+                let mut caster = SkillCaster::new(asset_server.load(BOXY_SKILLS_PATH))
+                    .with_slot(SKILL_1, Some("rot"))
+                    .with_slot(SKILL_2, Some("bloom"));
+                caster.unlock("rot");
+                caster
+            },
+            // bloom is only granted for a while by the bloom spell
+            TemporarySkills::new(asset_server.load(BOXY_TEMPORARY_SKILLS_PATH), USE_ITEM)
+                .with_cycle(CYCLE_ITEM),
+            Collector,
+            Respawnable::default(),
+            Health::new(100.0).with_invulnerability(1.0),
+            FallDamage::default(),
+        ))
+        .id();
+    camera_manager.set_follow(CameraMode::GAME, Some(CameraFollow::new(boxy)));
 
     // ground
     ground_entity.id = commands
@@ -181,6 +219,167 @@ fn setup(
         // PickingBehavior::IGNORE,
     ));
 
+    // tree, rotted away by the rot skill
+    commands.spawn((
+        Mesh3d(meshes.add(Cylinder::new(0.3, 2.0))),
+        MeshMaterial3d(materials.add(Color::srgb_u8(90, 60, 30))),
+        Transform::from_xyz(2.5, 1.0, 0.0),
+        Rottable::default(),
+        TollgateObstacle("grove_tree".to_string()),
+    ));
+
+    // sprout, grown into a platform by the bloom skill
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(1.5, 0.2, 1.5))),
+        MeshMaterial3d(materials.add(Color::srgb_u8(60, 160, 60))),
+        Transform::from_xyz(-2.5, 0.6, 0.0).with_scale(Vec3::splat(0.1)),
+        Bloomable::default(),
+    ));
+
+    // updraft, lifting Boxy while ballooning
+    commands.spawn((
+        Transform::from_xyz(0.0, 3.0, -3.0),
+        Updraft {
+            volume: BoxVolume::new(Vec3::new(1.0, 3.0, 1.0)),
+            strength: 25.0,
+        },
+    ));
+
+    // rock, blown up by bombs
+    commands.spawn((
+        Mesh3d(meshes.add(Sphere::new(0.5))),
+        MeshMaterial3d(materials.add(Color::srgb_u8(110, 110, 120))),
+        Transform::from_xyz(0.0, 0.5, 2.5),
+        Explodable,
+    ));
+
+    // temporary skill pickups
+    for (skill, charges, position, color) in [
+        (
+            "bomb",
+            3,
+            Vec3::new(2.0, 0.4, 2.0),
+            Color::srgb_u8(200, 60, 40),
+        ),
+        (
+            "bloom_spell",
+            1,
+            Vec3::new(-2.0, 0.4, 2.0),
+            Color::srgb_u8(60, 200, 120),
+        ),
+    ] {
+        commands.spawn((
+            Mesh3d(meshes.add(Cuboid::new(0.3, 0.3, 0.3))),
+            MeshMaterial3d(materials.add(color)),
+            Transform::from_translation(position),
+            TemporarySkillPickup {
+                skill: skill.to_string(),
+                charges,
+                radius: 0.8,
+            },
+            ControllerCollisionIgnore,
+        ));
+    }
+
+    // mushrooms and a quest item, the sap is only found once
+    for (i, angle) in [0.3_f32, 1.1, 1.9].into_iter().enumerate() {
+        commands.spawn((
+            Mesh3d(meshes.add(Sphere::new(0.15))),
+            MeshMaterial3d(materials.add(Color::srgb_u8(220, 120, 60))),
+            Transform::from_xyz(3.2 * angle.cos(), 0.2, 3.2 * angle.sin()),
+            Collectible::new("mushroom", 1 + i as u32),
+            ControllerCollisionIgnore,
+        ));
+    }
+    commands.spawn((
+        Mesh3d(meshes.add(Sphere::new(0.2))),
+        MeshMaterial3d(materials.add(Color::srgb_u8(240, 200, 40))),
+        Transform::from_xyz(-3.0, 0.3, -1.0),
+        Collectible::new("sap", 1).unique("sap_first_grove"),
+        ControllerCollisionIgnore,
+    ));
+
+    // tollgates: opened by rotting the tree, by a bomb, and by paying the troll
+    let gate_mesh = meshes.add(Cuboid::new(0.3, 2.0, 2.0));
+    let gate_material = materials.add(Color::srgb_u8(70, 50, 90));
+    for (gate, position) in [
+        (
+            Tollgate::new(
+                "grove_gate",
+                UnlockCondition::Rot {
+                    obstacle: "grove_tree".to_string(),
+                },
+            ),
+            Vec3::new(3.6, 1.0, 0.0),
+        ),
+        (
+            Tollgate::new("rubble_gate", UnlockCondition::Explosion),
+            Vec3::new(-3.6, 1.0, 0.0),
+        ),
+        (
+            Tollgate::new(
+                "troll_gate",
+                UnlockCondition::Toll {
+                    kind: "mushroom".to_string(),
+                    count: 3,
+                },
+            )
+            .with_reveal(asset_server.load(REVEAL_RAIL_PATH), 1.5),
+            Vec3::new(0.0, 1.0, -3.6),
+        ),
+    ] {
+        let rotation = Quat::from_rotation_y(-position.z.atan2(position.x));
+        commands.spawn((
+            Mesh3d(gate_mesh.clone()),
+            MeshMaterial3d(gate_material.clone()),
+            Transform::from_translation(position).with_rotation(rotation),
+            gate,
+        ));
+    }
+
+    // checkpoint by the updraft, falling off the ground respawns here
+    respawn_settings.world_bounds =
+        Some((Vec3::new(-20.0, -10.0, -20.0), Vec3::new(20.0, 30.0, 20.0)));
+    commands.spawn((
+        Transform::from_xyz(-2.0, 1.0, -2.0),
+        Checkpoint {
+            id: "updraft".to_string(),
+            volume: BoxVolume::new(Vec3::new(0.75, 1.0, 0.75)),
+            offset: Vec3::ZERO,
+        },
+    ));
+
+    // hazards: spikes and a rot pool
+    for (position, size, color, kind, damage) in [
+        (
+            Vec3::new(2.2, 0.1, -2.2),
+            Vec3::new(1.0, 0.2, 1.0),
+            Color::srgb_u8(160, 160, 170),
+            DamageType::Spikes,
+            25.0,
+        ),
+        (
+            Vec3::new(1.0, 0.05, -1.6),
+            Vec3::new(0.8, 0.1, 0.8),
+            Color::srgb_u8(80, 110, 40),
+            DamageType::Rot,
+            10.0,
+        ),
+    ] {
+        commands.spawn((
+            Mesh3d(meshes.add(Cuboid::from_size(size))),
+            MeshMaterial3d(materials.add(color)),
+            Transform::from_translation(position),
+            Hazard {
+                volume: BoxVolume::new(size / 2.0 + Vec3::Y * 0.5),
+                damage,
+                kind,
+                knockback: 6.0,
+            },
+            ControllerCollisionIgnore,
+        ));
+    }
+
     // light
     commands.spawn((
         PointLight {
@@ -197,24 +396,6 @@ fn setup(
     // ));
 }
 
-fn setup_once_loaded(
-    mut commands: Commands,
-    animations: Res<Animations>,
-    mut players: Query<(Entity, &mut AnimationPlayer), Added<AnimationPlayer>>,
-) {
-    // println!("AnimationPlayer loaded...");
-    for (entity, mut player) in &mut players {
-        let mut transitions = AnimationTransitions::new();
-        transitions
-            .play(&mut player, animations.animations[0], Duration::ZERO)
-            .repeat();
-        commands
-            .entity(entity)
-            .insert(AnimationGraphHandle(animations.graph.clone()))
-            .insert(transitions);
-    }
-}
-
 fn draw_cursor(
     pointers: Query<&PointerInteraction>,
     mut gizmos: Gizmos,
@@ -246,24 +427,3 @@ fn draw_cursor(
 fn rotate_boxy(_time: Res<Time>, _boxy: Single<&mut Transform, With<Boxy>>) {
     // boxy.rotate_y(0.2 * TAU * time.delta_secs());
 }
-
-fn keyboard_animation_control(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
-    animations: Res<Animations>,
-    mut current_animation: Local<usize>,
-) {
-    for (mut player, mut transitions) in &mut animation_players {
-        if keyboard_input.just_pressed(KeyCode::Enter) {
-            *current_animation = (*current_animation + 1) % animations.animations.len();
-
-            transitions
-                .play(
-                    &mut player,
-                    animations.animations[*current_animation],
-                    Duration::ZERO,
-                )
-                .repeat();
-        }
-    }
-}
@@ -0,0 +1,387 @@
+use std::collections::BTreeMap;
+
+use bevy::{animation::RepeatAnimation, gltf::Gltf, prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use crate::{player_controller::PlayerController, ron_asset::RonAssetPlugin};
+
+pub const IDLE: &str = "idle";
+pub const WALK: &str = "walk";
+pub const RUN: &str = "run";
+pub const JUMP: &str = "jump";
+pub const FALL: &str = "fall";
+pub const LAND: &str = "land";
+pub const GLIDE: &str = "glide";
+
+pub struct CharacterAnimationPlugin;
+
+impl Plugin for CharacterAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<AnimationStates>::new(&["anim.ron"]))
+            .add_systems(
+                Update,
+                (reload_graphs, build_graphs, update_animators).chain(),
+            );
+    }
+}
+
+/**
+ * Animation states of a character, loaded from `*.anim.ron` files. States
+ * are keyed by the constants in this module, skill casts by their own names
+ */
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AnimationStates {
+    // seconds, used by states without their own crossfade
+    pub crossfade: f32,
+    // horizontal speed below which the character idles
+    pub idle_speed: f32,
+    // walk blends into run between these speeds
+    pub walk_speed: f32,
+    pub run_speed: f32,
+    pub states: BTreeMap<String, AnimationStateDef>,
+}
+
+impl Default for AnimationStates {
+    fn default() -> Self {
+        Self {
+            crossfade: 0.2,
+            idle_speed: 0.1,
+            walk_speed: 2.,
+            run_speed: 5.,
+            states: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AnimationStateDef {
+    // name of the clip in the glTF
+    pub clip: String,
+    pub crossfade: Option<f32>,
+    pub speed: f32,
+    // playback speed scales with horizontal speed relative to this
+    pub reference_speed: Option<f32>,
+    pub repeat: bool,
+    // one-shot states like land and casts end after this long, or when the clip finishes
+    pub duration: Option<f32>,
+}
+
+impl Default for AnimationStateDef {
+    fn default() -> Self {
+        Self {
+            clip: String::new(),
+            crossfade: None,
+            speed: 1.,
+            reference_speed: None,
+            repeat: true,
+            duration: None,
+        }
+    }
+}
+
+impl AnimationStates {
+    /**
+     * The state to play for `state`, falling back to a similar one
+     * when it isn't defined, e.g. run to walk to idle
+     */
+    fn resolve<'a>(&self, state: &'a str) -> Option<&'a str> {
+        let mut state = state;
+        loop {
+            if self.states.contains_key(state) {
+                return Some(state);
+            }
+            state = match state {
+                RUN => WALK,
+                WALK => IDLE,
+                GLIDE => FALL,
+                FALL => JUMP,
+                JUMP => IDLE,
+                _ => return None,
+            };
+        }
+    }
+
+    fn crossfade(&self, state: &str) -> f32 {
+        self.states
+            .get(state)
+            .and_then(|def| def.crossfade)
+            .unwrap_or(self.crossfade)
+    }
+}
+
+#[derive(Debug)]
+struct AnimatorGraph {
+    nodes: HashMap<String, AnimationNodeIndex>,
+    player: Entity,
+}
+
+/**
+ * Drives the AnimationPlayer in a character's scene from its
+ * PlayerController, if it has one, and from requested casts
+ */
+#[derive(Component, Debug)]
+pub struct CharacterAnimator {
+    states: Handle<AnimationStates>,
+    gltf: Handle<Gltf>,
+    graph: Option<AnimatorGraph>,
+    current: String,
+    elapsed: f32,
+    requested_cast: Option<String>,
+    was_grounded: bool,
+    weights: HashMap<AnimationNodeIndex, f32>,
+}
+
+impl CharacterAnimator {
+    pub fn new(states: Handle<AnimationStates>, gltf: Handle<Gltf>) -> Self {
+        Self {
+            states,
+            gltf,
+            graph: None,
+            current: IDLE.to_string(),
+            elapsed: 0.,
+            requested_cast: None,
+            was_grounded: true,
+            weights: HashMap::default(),
+        }
+    }
+
+    pub fn state(&self) -> &str {
+        &self.current
+    }
+
+    /**
+     * Plays a one-shot cast state, ignored if the state isn't defined
+     */
+    pub fn play_cast(&mut self, name: &str) {
+        self.requested_cast = Some(name.to_string());
+    }
+}
+
+/**
+ * Drops the graphs of animators whose states or glTF were hot-reloaded,
+ * so build_graphs builds them again
+ */
+fn reload_graphs(
+    mut ev_states: EventReader<AssetEvent<AnimationStates>>,
+    mut ev_gltfs: EventReader<AssetEvent<Gltf>>,
+    mut animators: Query<&mut CharacterAnimator>,
+    mut players: Query<&mut AnimationPlayer>,
+) {
+    let modified_states = ev_states
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let modified_gltfs = ev_gltfs
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if modified_states.is_empty() && modified_gltfs.is_empty() {
+        return;
+    }
+    for mut animator in &mut animators {
+        if !modified_states.contains(&animator.states.id())
+            && !modified_gltfs.contains(&animator.gltf.id())
+        {
+            continue;
+        }
+        let Some(graph) = animator.graph.take() else {
+            continue;
+        };
+        if let Ok(mut player) = players.get_mut(graph.player) {
+            player.stop_all();
+        }
+        animator.weights.clear();
+    }
+}
+
+fn build_graphs(
+    mut commands: Commands,
+    mut animators: Query<(Entity, &mut CharacterAnimator)>,
+    children: Query<&Children>,
+    players: Query<(), With<AnimationPlayer>>,
+    states: Res<Assets<AnimationStates>>,
+    gltfs: Res<Assets<Gltf>>,
+    mut graphs: ResMut<Assets<AnimationGraph>>,
+) {
+    for (entity, mut animator) in &mut animators {
+        if animator.graph.is_some() {
+            continue;
+        }
+        let (Some(states), Some(gltf)) = (states.get(&animator.states), gltfs.get(&animator.gltf))
+        else {
+            continue;
+        };
+        // the scene may not have spawned yet
+        let Some(player) = children
+            .iter_descendants(entity)
+            .find(|e| players.contains(*e))
+        else {
+            continue;
+        };
+
+        let mut graph = AnimationGraph::new();
+        let mut nodes = HashMap::default();
+        for (name, def) in &states.states {
+            let Some(clip) = gltf.named_animations.get(def.clip.as_str()) else {
+                warn!("Animation state {} uses missing clip {}", name, def.clip);
+                continue;
+            };
+            nodes.insert(name.clone(), graph.add_clip(clip.clone(), 0., graph.root));
+        }
+        commands
+            .entity(player)
+            .insert(AnimationGraphHandle(graphs.add(graph)));
+        animator.graph = Some(AnimatorGraph { nodes, player });
+    }
+}
+
+fn update_animators(
+    time: Res<Time>,
+    states: Res<Assets<AnimationStates>>,
+    mut animators: Query<(&mut CharacterAnimator, Option<&PlayerController>)>,
+    mut players: Query<&mut AnimationPlayer>,
+) {
+    let delta = time.delta_secs();
+    for (mut animator, controller) in &mut animators {
+        let animator = &mut *animator;
+        let (Some(states), Some(graph)) = (states.get(&animator.states), &animator.graph) else {
+            continue;
+        };
+        let Ok(mut player) = players.get_mut(graph.player) else {
+            continue;
+        };
+        animator.elapsed += delta;
+
+        let (grounded, gliding, velocity) = controller.map_or((true, false, Vec3::ZERO), |c| {
+            (c.is_grounded(), c.is_gliding(), c.velocity())
+        });
+        let speed = Vec3::new(velocity.x, 0., velocity.z).length();
+        let landed = grounded && !animator.was_grounded;
+        animator.was_grounded = grounded;
+
+        // one-shot states run until their duration or clip ends
+        let one_shot = states
+            .states
+            .get(&animator.current)
+            .filter(|def| !def.repeat || def.duration.is_some());
+        let one_shot_running = one_shot.is_some_and(|def| match def.duration {
+            Some(duration) => animator.elapsed < duration,
+            None => graph
+                .nodes
+                .get(&animator.current)
+                .and_then(|node| player.animation(*node))
+                .is_some_and(|active| !active.is_finished()),
+        });
+
+        let cast = animator
+            .requested_cast
+            .take()
+            .and_then(|cast| states.states.contains_key(&cast).then_some(cast));
+        let locomotion = if !grounded {
+            if gliding {
+                GLIDE
+            } else if velocity.y > 0. {
+                JUMP
+            } else {
+                FALL
+            }
+        } else if landed && states.states.contains_key(LAND) {
+            LAND
+        } else if speed < states.idle_speed {
+            IDLE
+        } else if speed < (states.walk_speed + states.run_speed) * 0.5 {
+            WALK
+        } else {
+            RUN
+        };
+        let recast = cast.is_some();
+        let interrupt = landed || (!grounded && animator.current == LAND);
+        let next = match cast {
+            Some(cast) => Some(cast),
+            None if !one_shot_running || interrupt => {
+                states.resolve(locomotion).map(str::to_string)
+            }
+            None => None,
+        };
+        // casts restart when requested again while playing
+        let mut entered = false;
+        if let Some(next) = next {
+            if next != animator.current || recast {
+                animator.current = next;
+                animator.elapsed = 0.;
+                entered = true;
+            }
+        }
+        let current = animator.current.clone();
+
+        // target weights, walk and run are mixed by speed
+        let mut targets: HashMap<AnimationNodeIndex, f32> = HashMap::default();
+        let run_mix = ((speed - states.walk_speed)
+            / (states.run_speed - states.walk_speed).max(0.01))
+        .clamp(0., 1.);
+        let mix = match current.as_str() {
+            WALK | RUN
+                if states.resolve(RUN) == Some(RUN) && states.resolve(WALK) == Some(WALK) =>
+            {
+                vec![(WALK, 1. - run_mix), (RUN, run_mix)]
+            }
+            state => vec![(state, 1.)],
+        };
+        for (state, weight) in mix {
+            if let Some(node) = graph.nodes.get(state) {
+                *targets.entry(*node).or_default() += weight;
+            }
+        }
+
+        let fade = states.crossfade(&current);
+        let step = if fade > 0. { delta / fade } else { 1. };
+        for node in targets.keys() {
+            animator.weights.entry(*node).or_insert(0.);
+        }
+        animator.weights.retain(|node, weight| {
+            let target = targets.get(node).copied().unwrap_or(0.);
+            *weight = if *weight < target {
+                (*weight + step).min(target)
+            } else {
+                (*weight - step).max(target)
+            };
+            if *weight <= 0. && target <= 0. {
+                player.stop(*node);
+                return false;
+            }
+            true
+        });
+
+        // a state removed by a hot reload fades out with default settings
+        // until its graph is rebuilt
+        let removed = AnimationStateDef::default();
+        for (state, node) in &graph.nodes {
+            let Some(weight) = animator.weights.get(node) else {
+                continue;
+            };
+            let def = states.states.get(state).unwrap_or(&removed);
+            let one_shot = !def.repeat || def.duration.is_some();
+            if entered && one_shot && *state == current {
+                player.start(*node);
+            }
+            let active = player.play(*node);
+            if def.repeat {
+                active.repeat();
+            } else {
+                active.set_repeat(RepeatAnimation::Never);
+            }
+            let scale = def
+                .reference_speed
+                .map_or(1., |reference| speed / reference.max(0.01));
+            active.set_weight(*weight).set_speed(def.speed * scale);
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::easing::Easing;
+
+pub mod persistence;
+
+pub struct CollectiblesPlugin;
+
+impl Plugin for CollectiblesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wallets>()
+            .init_resource::<persistence::CollectedItems>()
+            .add_event::<Collected>()
+            .add_systems(PreStartup, persistence::load_collected_items)
+            .add_systems(
+                Update,
+                (persistence::remove_collected, collect, animate_pickups).chain(),
+            )
+            .add_systems(
+                Last,
+                persistence::save_collected_items.after(crate::exit_game::ExitGameSystem),
+            );
+    }
+}
+
+/**
+ * Item picked up by a Collector coming within `radius`, e.g. a mushroom or
+ * a quest item. Unique items are remembered across sessions and never spawn again
+ */
+#[derive(Component, Debug, Clone)]
+pub struct Collectible {
+    pub kind: String,
+    pub value: u32,
+    pub radius: f32,
+    pub unique_id: Option<String>,
+    // seconds of the fly-to-collector animation
+    pub pickup_time: f32,
+}
+
+impl Collectible {
+    pub fn new(kind: &str, value: u32) -> Self {
+        Self {
+            kind: kind.to_string(),
+            value,
+            radius: 0.6,
+            unique_id: None,
+            pickup_time: 0.4,
+        }
+    }
+
+    pub fn unique(mut self, id: &str) -> Self {
+        self.unique_id = Some(id.to_string());
+        self
+    }
+
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+}
+
+/**
+ * Character able to pick up collectibles, the tally goes to its wallet
+ */
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Collector;
+
+/**
+ * Collected counts by kind
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Wallet {
+    counts: BTreeMap<String, u32>,
+}
+
+impl Wallet {
+    pub fn count(&self, kind: &str) -> u32 {
+        self.counts.get(kind).copied().unwrap_or(0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.counts
+            .iter()
+            .map(|(kind, count)| (kind.as_str(), *count))
+    }
+
+    pub fn add(&mut self, kind: &str, count: u32) {
+        *self.counts.entry(kind.to_string()).or_default() += count;
+    }
+
+    /**
+     * Takes `count` of a kind, returns false and leaves the wallet as is
+     * if there isn't enough
+     */
+    pub fn spend(&mut self, kind: &str, count: u32) -> bool {
+        if self.count(kind) < count {
+            return false;
+        }
+        if let Some(current) = self.counts.get_mut(kind) {
+            *current -= count;
+        }
+        true
+    }
+}
+
+/**
+ * Wallets of every Collector
+ */
+#[derive(Resource, Debug, Default)]
+pub struct Wallets(HashMap<Entity, Wallet>);
+
+impl Wallets {
+    pub fn get(&self, collector: Entity) -> Option<&Wallet> {
+        self.0.get(&collector)
+    }
+
+    pub fn get_mut(&mut self, collector: Entity) -> &mut Wallet {
+        self.0.entry(collector).or_default()
+    }
+
+    pub fn count(&self, collector: Entity, kind: &str) -> u32 {
+        self.get(collector).map_or(0, |wallet| wallet.count(kind))
+    }
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct Collected {
+    pub collector: Entity,
+    pub entity: Entity,
+    pub kind: String,
+    pub value: u32,
+    pub unique_id: Option<String>,
+}
+
+/**
+ * Collectible on its way to the collector, despawned once it arrives
+ */
+#[derive(Component)]
+pub struct PickingUp {
+    collector: Entity,
+    elapsed: f32,
+    duration: f32,
+    // translation and scale when picked up, taken on the first update
+    from: Option<(Vec3, Vec3)>,
+}
+
+fn collect(
+    mut commands: Commands,
+    mut wallets: ResMut<Wallets>,
+    mut collected_items: ResMut<persistence::CollectedItems>,
+    collectibles: Query<(Entity, &Collectible, &GlobalTransform)>,
+    collectors: Query<(Entity, &GlobalTransform), With<Collector>>,
+    mut ev_collected: EventWriter<Collected>,
+) {
+    for (entity, collectible, global) in &collectibles {
+        let Some((collector, _)) = collectors.iter().find(|(_, collector)| {
+            collector.translation().distance(global.translation()) <= collectible.radius
+        }) else {
+            continue;
+        };
+        wallets
+            .get_mut(collector)
+            .add(&collectible.kind, collectible.value);
+        if let Some(id) = &collectible.unique_id {
+            collected_items.insert(id);
+        }
+        ev_collected.send(Collected {
+            collector,
+            entity,
+            kind: collectible.kind.clone(),
+            value: collectible.value,
+            unique_id: collectible.unique_id.clone(),
+        });
+        commands
+            .entity(entity)
+            .remove::<Collectible>()
+            .insert(PickingUp {
+                collector,
+                elapsed: 0.,
+                duration: collectible.pickup_time,
+                from: None,
+            });
+    }
+}
+
+fn animate_pickups(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut pickups: Query<(Entity, &mut PickingUp, &mut Transform)>,
+    collectors: Query<&GlobalTransform>,
+) {
+    for (entity, mut state, mut transform) in &mut pickups {
+        let (from, from_scale) = *state
+            .from
+            .get_or_insert((transform.translation, transform.scale));
+        state.elapsed += time.delta_secs();
+        let t = if state.duration > 0. {
+            state.elapsed / state.duration
+        } else {
+            1.
+        };
+        if t >= 1. {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        // pops up, then gets pulled into the collector
+        let target = collectors
+            .get(state.collector)
+            .map_or(from, |collector| collector.translation() + Vec3::Y);
+        let arc = Vec3::Y * (t * std::f32::consts::PI).sin() * 0.5;
+        transform.translation = from.lerp(target, Easing::QuadraticIn.sample(t)) + arc;
+        transform.scale = from_scale * (1. - Easing::QuadraticIn.sample(t));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn spending_takes_from_the_wallet() {
+        let mut wallet = Wallet::default();
+        wallet.add("shroom", 3);
+        wallet.add("shroom", 2);
+        assert!(wallet.spend("shroom", 4));
+        assert_eq!(wallet.count("shroom"), 1);
+        assert!(wallet.spend("shroom", 1));
+        assert_eq!(wallet.count("shroom"), 0);
+    }
+
+    #[test]
+    fn overspending_leaves_the_wallet_as_is() {
+        let mut wallet = Wallet::default();
+        wallet.add("shroom", 3);
+        assert!(!wallet.spend("shroom", 4));
+        assert_eq!(wallet.count("shroom"), 3);
+        assert!(!wallet.spend("gem", 1));
+        assert_eq!(wallet.count("gem"), 0);
+        assert!(wallet.spend("gem", 0));
+    }
+
+    #[test]
+    fn collectors_in_reach_pick_up_into_their_wallet() {
+        let mut world = World::new();
+        world.init_resource::<Wallets>();
+        world.init_resource::<persistence::CollectedItems>();
+        world.init_resource::<Events<Collected>>();
+        let collector = world
+            .spawn((Collector, GlobalTransform::from_xyz(0., 0., 0.)))
+            .id();
+        let near = world
+            .spawn((
+                Collectible::new("shroom", 2).unique("first_shroom"),
+                GlobalTransform::from_xyz(0.5, 0., 0.),
+            ))
+            .id();
+        let far = world
+            .spawn((
+                Collectible::new("shroom", 5),
+                GlobalTransform::from_xyz(3., 0., 0.),
+            ))
+            .id();
+
+        world.run_system_once(collect).unwrap();
+        assert_eq!(world.resource::<Wallets>().count(collector, "shroom"), 2);
+        assert!(world
+            .resource::<persistence::CollectedItems>()
+            .contains("first_shroom"));
+        assert!(world.get::<PickingUp>(near).is_some());
+        assert!(world.get::<Collectible>(far).is_some());
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeSet;
+
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use super::{Collected, Collectible};
+use crate::ron_asset::{load_ron_file, save_ron_file};
+
+pub const COLLECTED_ITEMS_PATH: &str = "collected_items.ron";
+
+/**
+ * Ids of the unique collectibles picked up so far, kept across sessions
+ */
+#[derive(Resource, Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CollectedItems {
+    pub unique: BTreeSet<String>,
+}
+
+impl CollectedItems {
+    pub fn contains(&self, id: &str) -> bool {
+        self.unique.contains(id)
+    }
+
+    pub fn insert(&mut self, id: &str) {
+        self.unique.insert(id.to_string());
+    }
+}
+
+pub(super) fn load_collected_items(mut collected_items: ResMut<CollectedItems>) {
+    if let Some(items) = load_ron_file::<CollectedItems>(COLLECTED_ITEMS_PATH) {
+        *collected_items = items;
+    }
+}
+
+/**
+ * Unique collectibles collected in an earlier session are despawned as they appear
+ */
+pub(super) fn remove_collected(
+    mut commands: Commands,
+    collected_items: Res<CollectedItems>,
+    collectibles: Query<(Entity, &Collectible), Added<Collectible>>,
+) {
+    for (entity, collectible) in &collectibles {
+        if collectible
+            .unique_id
+            .as_deref()
+            .is_some_and(|id| collected_items.contains(id))
+        {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/**
+ * Written as soon as a unique item is picked up, and on exit
+ */
+pub(super) fn save_collected_items(
+    ev_exit: EventReader<AppExit>,
+    mut ev_collected: EventReader<Collected>,
+    collected_items: Res<CollectedItems>,
+) {
+    // counted rather than any(), so no event is left unread for the next frame
+    let picked_unique = ev_collected
+        .read()
+        .filter(|collected| collected.unique_id.is_some())
+        .count()
+        > 0;
+    if !picked_unique && ev_exit.is_empty() {
+        return;
+    }
+    save_ron_file(COLLECTED_ITEMS_PATH, &*collected_items);
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Easing {
+    Linear,
+    QuadraticIn,
+    QuadraticOut,
+    #[default]
+    QuadraticInOut,
+    CubicInOut,
+    SmoothStep,
+}
+
+impl Easing {
+    /**
+     * Maps linear progress in [0, 1] to eased progress in [0, 1]
+     */
+    pub fn sample(&self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Self::Linear => t,
+            Self::QuadraticIn => t * t,
+            Self::QuadraticOut => 1. - (1. - t) * (1. - t),
+            Self::QuadraticInOut => {
+                if t < 0.5 {
+                    2. * t * t
+                } else {
+                    1. - (-2. * t + 2.).powi(2) / 2.
+                }
+            }
+            Self::CubicInOut => {
+                if t < 0.5 {
+                    4. * t * t * t
+                } else {
+                    1. - (-2. * t + 2.).powi(3) / 2.
+                }
+            }
+            Self::SmoothStep => t * t * (3. - 2. * t),
+        }
+    }
+}
+
+/**
+ * Interpolates between two angles in degrees along the shortest arc
+ */
+pub fn lerp_angle(from: f32, to: f32, t: f32) -> f32 {
+    let delta = (to - from + 180.).rem_euclid(360.) - 180.;
+    from + delta * t
+}
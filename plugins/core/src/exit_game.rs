@@ -12,10 +12,16 @@ impl Plugin for ExitGamePlugin {
         app.add_event::<ExitGameEvent>()
             .add_systems(Startup, register_input)
             .add_systems(Update, read_input)
-            .add_systems(Last, exit_game);
+            .add_systems(Last, exit_game.in_set(ExitGameSystem));
     }
 }
 
+/**
+ * Label of the system sending AppExit, for saving state after it in `Last`
+ */
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ExitGameSystem;
+
 #[derive(Event)]
 struct ExitGameEvent;
 
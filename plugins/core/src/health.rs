@@ -0,0 +1,359 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{player_controller::PlayerController, respawn::PlayerRespawned};
+
+pub mod hazards;
+
+/**
+ * Health, damage and death. Also reads Explosion, PlayerLanded and
+ * PlayerRespawned, registered by the temporary skills, player controller
+ * and respawn plugins
+ */
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Damage>()
+            .add_event::<Damaged>()
+            .add_event::<Died>()
+            .add_systems(
+                Update,
+                (
+                    tick_invulnerability,
+                    hazards::hurt_in_hazards,
+                    hazards::explosion_damage,
+                    hazards::fall_damage,
+                    apply_damage,
+                    revive_respawned,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DamageType {
+    Physical,
+    Explosion,
+    Spikes,
+    Rot,
+    Fall,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Health {
+    pub max: f32,
+    // seconds a character can't be hurt after being hit
+    pub invulnerability: f32,
+    current: f32,
+    invulnerable: f32,
+    dead: bool,
+    // multipliers on damage taken, 0 for immunity
+    resistances: HashMap<DamageType, f32>,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self {
+            max,
+            invulnerability: 0.5,
+            current: max,
+            invulnerable: 0.,
+            dead: false,
+            resistances: HashMap::default(),
+        }
+    }
+
+    pub fn with_invulnerability(mut self, seconds: f32) -> Self {
+        self.invulnerability = seconds;
+        self
+    }
+
+    pub fn with_resistance(mut self, kind: DamageType, multiplier: f32) -> Self {
+        self.resistances.insert(kind, multiplier);
+        self
+    }
+
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0. {
+            self.current / self.max
+        } else {
+            0.
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.dead
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable > 0.
+    }
+
+    pub fn resistance(&self, kind: DamageType) -> f32 {
+        self.resistances.get(&kind).copied().unwrap_or(1.)
+    }
+
+    pub fn heal(&mut self, amount: f32) {
+        if !self.dead {
+            self.current = (self.current + amount).min(self.max);
+        }
+    }
+
+    /**
+     * Back to full health, invulnerable for a moment
+     */
+    pub fn revive(&mut self) {
+        self.current = self.max;
+        self.dead = false;
+        self.invulnerable = self.invulnerability;
+    }
+
+    /**
+     * Takes a hit, returns the damage dealt or None if it was ignored
+     * because of i-frames, immunity or being dead already
+     */
+    pub fn take(&mut self, amount: f32, kind: DamageType) -> Option<f32> {
+        if self.dead || self.is_invulnerable() {
+            return None;
+        }
+        let amount = amount * self.resistance(kind);
+        if amount <= 0. {
+            return None;
+        }
+        let dealt = amount.min(self.current);
+        self.current -= dealt;
+        self.invulnerable = self.invulnerability;
+        self.dead = self.current <= 0.;
+        Some(dealt)
+    }
+}
+
+/**
+ * Request to hurt an entity, sent by hazards or game code
+ */
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Damage {
+    pub target: Entity,
+    pub amount: f32,
+    pub kind: DamageType,
+    pub source: Option<Entity>,
+    // velocity added to player controllers that take the hit
+    pub knockback: Vec3,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Damaged {
+    pub entity: Entity,
+    pub amount: f32,
+    pub kind: DamageType,
+    pub source: Option<Entity>,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Died {
+    pub entity: Entity,
+    pub kind: DamageType,
+    pub source: Option<Entity>,
+}
+
+fn tick_invulnerability(time: Res<Time>, mut healths: Query<&mut Health>) {
+    for mut health in &mut healths {
+        if health.invulnerable > 0. {
+            health.invulnerable = (health.invulnerable - time.delta_secs()).max(0.);
+        }
+    }
+}
+
+fn apply_damage(
+    mut ev_damage: EventReader<Damage>,
+    mut healths: Query<(&mut Health, Option<&mut PlayerController>)>,
+    mut ev_damaged: EventWriter<Damaged>,
+    mut ev_died: EventWriter<Died>,
+) {
+    for damage in ev_damage.read() {
+        let Ok((mut health, controller)) = healths.get_mut(damage.target) else {
+            continue;
+        };
+        let Some(amount) = health.take(damage.amount, damage.kind) else {
+            continue;
+        };
+        if let Some(mut controller) = controller.filter(|_| damage.knockback != Vec3::ZERO) {
+            controller.add_impulse(damage.knockback);
+        }
+        ev_damaged.send(Damaged {
+            entity: damage.target,
+            amount,
+            kind: damage.kind,
+            source: damage.source,
+        });
+        if health.is_dead() {
+            ev_died.send(Died {
+                entity: damage.target,
+                kind: damage.kind,
+                source: damage.source,
+            });
+        }
+    }
+}
+
+fn revive_respawned(
+    mut ev_respawned: EventReader<PlayerRespawned>,
+    mut healths: Query<&mut Health>,
+) {
+    for event in ev_respawned.read() {
+        if let Ok(mut health) = healths.get_mut(event.entity) {
+            health.revive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::{player_controller::jump::PlayerLanded, temporary_skills::bombs::Explosion};
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, HealthPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )))
+            .add_event::<Explosion>()
+            .add_event::<PlayerLanded>()
+            .add_event::<PlayerRespawned>();
+        app.update();
+        app
+    }
+
+    fn hit(app: &mut App, target: Entity, amount: f32, kind: DamageType) {
+        app.world_mut().send_event(Damage {
+            target,
+            amount,
+            kind,
+            source: None,
+            knockback: Vec3::ZERO,
+        });
+    }
+
+    fn health(app: &App, entity: Entity) -> &Health {
+        app.world().get::<Health>(entity).unwrap()
+    }
+
+    fn drain<E: Event>(app: &mut App) -> Vec<E> {
+        app.world_mut()
+            .resource_mut::<Events<E>>()
+            .drain()
+            .collect()
+    }
+
+    #[test]
+    fn damage_is_scaled_per_type() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(
+                Health::new(100.)
+                    .with_invulnerability(0.)
+                    .with_resistance(DamageType::Spikes, 0.5)
+                    .with_resistance(DamageType::Rot, 0.),
+            )
+            .id();
+
+        hit(&mut app, entity, 10., DamageType::Physical);
+        hit(&mut app, entity, 10., DamageType::Spikes);
+        hit(&mut app, entity, 10., DamageType::Rot);
+        app.update();
+
+        assert_eq!(health(&app, entity).current(), 85.);
+        let damaged = drain::<Damaged>(&mut app)
+            .iter()
+            .map(|damaged| (damaged.kind, damaged.amount))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            damaged,
+            vec![(DamageType::Physical, 10.), (DamageType::Spikes, 5.)]
+        );
+    }
+
+    #[test]
+    fn hits_are_ignored_during_invulnerability() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(Health::new(100.).with_invulnerability(0.5))
+            .id();
+
+        hit(&mut app, entity, 10., DamageType::Physical);
+        app.update();
+        assert_eq!(health(&app, entity).current(), 90.);
+        assert!(health(&app, entity).is_invulnerable());
+
+        hit(&mut app, entity, 10., DamageType::Physical);
+        app.update();
+        assert_eq!(health(&app, entity).current(), 90.);
+
+        for _ in 0..6 {
+            app.update();
+        }
+        assert!(!health(&app, entity).is_invulnerable());
+        hit(&mut app, entity, 10., DamageType::Physical);
+        app.update();
+        assert_eq!(health(&app, entity).current(), 80.);
+    }
+
+    #[test]
+    fn died_is_sent_once() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(Health::new(20.).with_invulnerability(0.))
+            .id();
+
+        hit(&mut app, entity, 15., DamageType::Physical);
+        hit(&mut app, entity, 15., DamageType::Fall);
+        hit(&mut app, entity, 15., DamageType::Physical);
+        app.update();
+        hit(&mut app, entity, 15., DamageType::Physical);
+        app.update();
+
+        assert!(health(&app, entity).is_dead());
+        assert_eq!(health(&app, entity).current(), 0.);
+        let died = drain::<Died>(&mut app);
+        assert_eq!(died.len(), 1);
+        assert_eq!(died[0].entity, entity);
+        assert_eq!(died[0].kind, DamageType::Fall);
+    }
+
+    #[test]
+    fn respawning_restores_health() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(Health::new(20.).with_invulnerability(0.5))
+            .id();
+
+        hit(&mut app, entity, 50., DamageType::Explosion);
+        app.update();
+        assert!(health(&app, entity).is_dead());
+
+        app.world_mut().send_event(PlayerRespawned {
+            entity,
+            checkpoint: None,
+        });
+        app.update();
+
+        let health = health(&app, entity);
+        assert!(!health.is_dead());
+        assert_eq!(health.current(), 20.);
+        assert!(health.is_invulnerable());
+    }
+}
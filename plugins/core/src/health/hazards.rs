@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+
+use super::{Damage, DamageType, Health};
+use crate::{
+    player_controller::jump::PlayerLanded, temporary_skills::bombs::Explosion, volume::BoxVolume,
+};
+
+// knockback speed of an explosion at its center
+const EXPLOSION_KNOCKBACK: f32 = 10.;
+
+/**
+ * Box volume, centered on the entity, hurting everything with Health inside
+ * it, e.g. spikes or a rot pool. I-frames keep the damage from applying every frame
+ */
+#[derive(Component, Debug, Clone)]
+pub struct Hazard {
+    pub volume: BoxVolume,
+    pub damage: f32,
+    pub kind: DamageType,
+    // speed characters are pushed out and up with
+    pub knockback: f32,
+}
+
+/**
+ * Hurts characters landing faster than `safe_speed`
+ */
+#[derive(Component, Debug, Clone)]
+pub struct FallDamage {
+    pub safe_speed: f32,
+    // damage per m/s of impact speed above `safe_speed`
+    pub damage_per_speed: f32,
+}
+
+impl Default for FallDamage {
+    fn default() -> Self {
+        Self {
+            safe_speed: 15.,
+            damage_per_speed: 5.,
+        }
+    }
+}
+
+pub(super) fn hurt_in_hazards(
+    hazards: Query<(Entity, &Hazard, &GlobalTransform)>,
+    targets: Query<(Entity, &Health, &GlobalTransform)>,
+    mut ev_damage: EventWriter<Damage>,
+) {
+    for (target, health, global) in &targets {
+        if health.is_dead() || health.is_invulnerable() {
+            continue;
+        }
+        let position = global.translation();
+        for (entity, hazard, hazard_transform) in &hazards {
+            if !hazard.volume.contains(hazard_transform, position) {
+                continue;
+            }
+            let away = (position - hazard_transform.translation())
+                .with_y(0.)
+                .normalize_or_zero();
+            ev_damage.send(Damage {
+                target,
+                amount: hazard.damage,
+                kind: hazard.kind,
+                source: Some(entity),
+                knockback: (away + Vec3::Y).normalize() * hazard.knockback,
+            });
+        }
+    }
+}
+
+/**
+ * Explosions hurt less and push less towards their edge
+ */
+pub(super) fn explosion_damage(
+    mut ev_explosion: EventReader<Explosion>,
+    targets: Query<(Entity, &GlobalTransform), With<Health>>,
+    mut ev_damage: EventWriter<Damage>,
+) {
+    for explosion in ev_explosion.read() {
+        for (target, global) in &targets {
+            let offset = global.translation() - explosion.point;
+            let distance = offset.length();
+            if distance > explosion.radius {
+                continue;
+            }
+            let falloff = if explosion.radius > 0. {
+                1. - 0.5 * distance / explosion.radius
+            } else {
+                1.
+            };
+            let away = offset.with_y(0.).normalize_or_zero();
+            ev_damage.send(Damage {
+                target,
+                amount: explosion.damage * falloff,
+                kind: DamageType::Explosion,
+                source: Some(explosion.source),
+                knockback: (away + Vec3::Y).normalize() * EXPLOSION_KNOCKBACK * falloff,
+            });
+        }
+    }
+}
+
+pub(super) fn fall_damage(
+    mut ev_landed: EventReader<PlayerLanded>,
+    fall_damages: Query<&FallDamage>,
+    mut ev_damage: EventWriter<Damage>,
+) {
+    for landed in ev_landed.read() {
+        let Ok(fall_damage) = fall_damages.get(landed.entity) else {
+            continue;
+        };
+        let excess = landed.impact_speed - fall_damage.safe_speed;
+        if excess <= 0. {
+            continue;
+        }
+        ev_damage.send(Damage {
+            target: landed.entity,
+            amount: excess * fall_damage.damage_per_speed,
+            kind: DamageType::Fall,
+            source: None,
+            knockback: Vec3::ZERO,
+        });
+    }
+}
@@ -4,21 +4,30 @@ use bevy::prelude::*;
 use bevy::utils::hashbrown::HashSet;
 use std::collections::HashMap;
 
+pub mod accessibility;
+
 pub struct InputManagerPlugin;
 impl Plugin for InputManagerPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(InputManager::default()).add_systems(
-            PreUpdate,
-            (
-                determine_input_mode,
-                button::read_button_input,
-                motion::read_motion_input,
-            ),
-        );
+        app.insert_resource(InputManager::default())
+            .add_systems(Startup, accessibility::load_binding_profile)
+            .add_systems(
+                PreUpdate,
+                (
+                    determine_input_mode,
+                    button::read_button_input,
+                    motion::read_motion_input,
+                    accessibility::apply_accessibility.after(button::read_button_input),
+                ),
+            )
+            .add_systems(
+                Last,
+                accessibility::save_binding_profile.after(crate::exit_game::ExitGameSystem),
+            );
     }
 }
 
-#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct Action(pub &'static str);
 
 #[derive(PartialEq, Eq, Hash, Clone, Copy)]
@@ -73,7 +82,7 @@ fn determine_input_mode(
     }
 
     // gamepad mode takes priority over MnK
-    if gamepad_events.len() != 0 {
+    if !gamepad_events.is_empty() {
         input_mode = Some(InputMode::Gamepad);
     }
 
@@ -89,6 +98,10 @@ pub struct InputManager {
     current_input_mode: InputMode,
     button_entries: HashMap<Action, button::ActionEntry>,
     motion_entries: HashMap<Action, motion::ActionEntry>,
+    chord_modifiers: HashMap<Action, Vec<button::Variant>>,
+    held_buttons: HashSet<button::Variant>,
+    frame_pressed_buttons: Vec<button::Variant>,
+    accessibility: accessibility::Layer,
 }
 
 impl InputManager {
@@ -107,6 +120,10 @@ impl Default for InputManager {
             current_input_mode: InputMode::MouseAndKeyboard,
             button_entries: HashMap::<Action, button::ActionEntry>::new(),
             motion_entries: HashMap::<Action, motion::ActionEntry>::new(),
+            chord_modifiers: HashMap::<Action, Vec<button::Variant>>::new(),
+            held_buttons: HashSet::<button::Variant>::new(),
+            frame_pressed_buttons: Vec::<button::Variant>::new(),
+            accessibility: accessibility::Layer::default(),
         }
     }
 }
@@ -123,7 +140,6 @@ impl InputManager {
                 motion_entries: entries
                     .iter()
                     .map(|e| (e.clone(), false)) // Disgusting clone
-                    .into_iter()
                     .collect(),
                 motion: Vec2::new(0., 0.),
             },
@@ -142,6 +158,16 @@ impl InputManager {
         unreachable!("Missing action: {}", action.0)
     }
 
+    /**
+     * Motion without normalization, e.g. mouse motion in pixels
+     */
+    pub fn get_motion_raw(&self, action: Action) -> Vec2 {
+        if let Some(entry) = self.motion_entries.get(&action) {
+            return entry.motion;
+        }
+        unreachable!("Missing action: {}", action.0)
+    }
+
     pub fn get_motion3z(&self, action: Action) -> Vec3 {
         let v2 = self.get_motion(action);
         Vec3 {
@@ -172,7 +198,24 @@ impl InputManager {
         );
     }
 
+    /**
+     * A chorded action is only pressed while all of its modifiers are held
+     * (or latched, when sticky modifiers are enabled)
+     */
+    pub fn register_action_chord(
+        &mut self,
+        action: Action,
+        modifiers: Vec<button::Variant>,
+        buttons: Vec<button::Variant>,
+    ) {
+        self.register_action_button(action, buttons);
+        self.chord_modifiers.insert(action, modifiers);
+    }
+
     pub fn is_action_pressed(&self, action: Action) -> bool {
+        if let Some(state) = self.accessibility.state(action) {
+            return state.pressed;
+        }
         if let Some(entry) = self.button_entries.get(&action) {
             return !entry.pressed.is_empty();
         }
@@ -180,6 +223,9 @@ impl InputManager {
     }
 
     pub fn is_action_just_pressed(&self, action: Action) -> bool {
+        if let Some(state) = self.accessibility.state(action) {
+            return state.just_pressed;
+        }
         if let Some(entry) = self.button_entries.get(&action) {
             return !entry.just_pressed.is_empty();
         }
@@ -187,13 +233,32 @@ impl InputManager {
     }
 
     pub fn is_action_just_released(&self, action: Action) -> bool {
+        if let Some(state) = self.accessibility.state(action) {
+            return state.just_released;
+        }
         if let Some(entry) = self.button_entries.get(&action) {
             return !entry.just_released.is_empty();
         }
         false
     }
 
+    pub fn accessibility(&self) -> &accessibility::Settings {
+        &self.accessibility.settings
+    }
+
+    /// Changes are written to the binding profile at the end of the frame
+    pub fn accessibility_mut(&mut self) -> &mut accessibility::Settings {
+        &mut self.accessibility.settings
+    }
+
+    /// Action currently highlighted by single-switch scanning, if enabled
+    pub fn scan_focus(&self) -> Option<Action> {
+        self.accessibility.scan_focus()
+    }
+
     fn set_button_pressed(&mut self, button: button::Variant) {
+        self.held_buttons.insert(button);
+        self.frame_pressed_buttons.push(button);
         for buttoninput in self.button_entries.values_mut() {
             for b in buttoninput.released.extract_if(|b| *b == button) {
                 buttoninput.just_pressed.insert(b);
@@ -209,6 +274,7 @@ impl InputManager {
     }
 
     fn set_button_released(&mut self, button: button::Variant) {
+        self.held_buttons.remove(&button);
         for buttoninput in self.button_entries.values_mut() {
             for b in buttoninput.pressed.extract_if(|b| *b == button) {
                 buttoninput.just_released.insert(b);
@@ -231,8 +297,9 @@ pub mod button {
         prelude::*,
         utils::HashSet,
     };
+    use serde::{Deserialize, Serialize};
 
-    #[derive(PartialEq, Eq, Hash, Clone, Copy)]
+    #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
     pub enum Variant {
         Keyboard(KeyCode),
         Mouse(MouseButton),
@@ -254,6 +321,7 @@ pub mod button {
     ) {
         input_manager.move_prev_frame_just_pressed();
         input_manager.move_prev_frame_just_released();
+        input_manager.frame_pressed_buttons.clear();
 
         for key in keyboard.get_just_pressed() {
             input_manager.set_button_pressed(Variant::Keyboard(*key));
@@ -274,15 +342,12 @@ pub mod button {
         }
 
         for event in gamepad.read() {
-            match event {
-                GamepadEvent::Button(button) => {
-                    if button.state.is_pressed() {
-                        input_manager.set_button_pressed(Variant::Gamepad(button.button));
-                    } else {
-                        input_manager.set_button_released(Variant::Gamepad(button.button));
-                    }
+            if let GamepadEvent::Button(button) = event {
+                if button.state.is_pressed() {
+                    input_manager.set_button_pressed(Variant::Gamepad(button.button));
+                } else {
+                    input_manager.set_button_released(Variant::Gamepad(button.button));
                 }
-                _ => (),
             }
         }
     }
@@ -347,15 +412,15 @@ pub mod motion {
 
     impl KeyCodeSet {
         fn is_key_pressed(&self, key: KeyCode) -> bool {
-            return self.pressed.contains(&key);
+            self.pressed.contains(&key)
         }
 
         fn is_key_released(&self, key: KeyCode) -> bool {
-            return self.released.contains(&key);
+            self.released.contains(&key)
         }
 
         fn is_empty(&self) -> bool {
-            return self.pressed.is_empty() && self.released.is_empty();
+            self.pressed.is_empty() && self.released.is_empty()
         }
     }
 
@@ -379,7 +444,7 @@ pub mod motion {
         pub(super) fn set_motion(
             &mut self,
             input_mode_priority: super::InputMode,
-            axis_events: &Vec<GamepadAxisChangedEvent>,
+            axis_events: &[GamepadAxisChangedEvent],
             mouse_motion: &Option<Vec2>,
             keyboard: &KeyCodeSet,
         ) {
@@ -412,8 +477,8 @@ pub mod motion {
 
         fn set_gamepad_axis_motion(
             motion: &mut Vec2,
-            relations: &Vec<Relation>,
-            axis_events: &Vec<GamepadAxisChangedEvent>,
+            relations: &[Relation],
+            axis_events: &[GamepadAxisChangedEvent],
         ) {
             for relation in relations {
                 if let Relation::GamepadAxis(relation_gamepad_axis, relation_axis) = relation {
@@ -461,7 +526,7 @@ pub mod motion {
         fn set_mouse_motion(
             motion: &mut Vec2,
             motion_last_frame: bool,
-            relations: &Vec<Relation>,
+            relations: &[Relation],
             mouse_motion: &Option<Vec2>,
         ) {
             assert!(relations.len() == 1);
@@ -483,9 +548,9 @@ pub mod motion {
     ) {
         let gamepad_axis_events = {
             let mut events = Vec::<GamepadAxisChangedEvent>::new();
-            for event in gamepad.read().into_iter() {
+            for event in gamepad.read() {
                 if let GamepadEvent::Axis(event) = event {
-                    events.push(event.clone());
+                    events.push(*event);
                 }
             }
             events
@@ -502,12 +567,10 @@ pub mod motion {
         let keycodes = KeyCodeSet {
             pressed: keyboard
                 .get_pressed()
-                .into_iter()
                 .cloned()
                 .collect::<HashSet<KeyCode>>(),
             released: keyboard
                 .get_just_released()
-                .into_iter()
                 .cloned()
                 .collect::<HashSet<KeyCode>>(),
         };
@@ -0,0 +1,453 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::utils::hashbrown::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+use super::{button, Action, InputManager};
+use crate::ron_asset::{load_ron_file, save_ron_file};
+
+pub const BINDING_PROFILE_PATH: &str = "binding_profile.ron";
+
+/**
+ * Accessibility options are stored by action name, so they can be persisted
+ * and loaded before the owning plugin has registered its actions
+ */
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub toggle_hold: BTreeSet<String>,
+    pub repeat_on_hold: HashMap<String, RepeatOnHold>,
+    pub sticky_modifiers: bool,
+    pub scanning: Option<Scanning>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RepeatOnHold {
+    // seconds held before the first repeat
+    pub delay: f32,
+    // repeats per second after the delay
+    pub rate: f32,
+}
+
+impl Default for RepeatOnHold {
+    fn default() -> Self {
+        Self {
+            delay: 0.5,
+            rate: 8.,
+        }
+    }
+}
+
+/**
+ * Single-switch scanning: focus cycles through `actions` every `interval`
+ * seconds, and holding `switch` presses whichever action has focus
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scanning {
+    pub switch: button::Variant,
+    pub interval: f32,
+    pub actions: Vec<String>,
+}
+
+impl Settings {
+    pub fn set_toggle_hold(&mut self, action: Action, enabled: bool) {
+        if enabled {
+            self.toggle_hold.insert(action.0.to_string());
+        } else {
+            self.toggle_hold.remove(action.0);
+        }
+    }
+
+    pub fn set_repeat_on_hold(&mut self, action: Action, repeat: Option<RepeatOnHold>) {
+        match repeat {
+            Some(repeat) => self.repeat_on_hold.insert(action.0.to_string(), repeat),
+            None => self.repeat_on_hold.remove(action.0),
+        };
+    }
+
+    pub fn set_scanning(&mut self, switch: button::Variant, interval: f32, actions: &[Action]) {
+        self.scanning = Some(Scanning {
+            switch,
+            interval,
+            actions: actions.iter().map(|a| a.0.to_string()).collect(),
+        });
+    }
+
+    fn is_layered(&self, action: Action) -> bool {
+        self.toggle_hold.contains(action.0)
+            || self.repeat_on_hold.contains_key(action.0)
+            || self
+                .scanning
+                .as_ref()
+                .is_some_and(|s| s.actions.iter().any(|a| a == action.0))
+    }
+}
+
+/**
+ * The player's binding profile as written to disk.
+ * Bindings themselves are still registered in code by each plugin
+ */
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BindingProfile {
+    pub accessibility: Settings,
+}
+
+impl InputManager {
+    pub fn binding_profile(&self) -> BindingProfile {
+        BindingProfile {
+            accessibility: self.accessibility.settings.clone(),
+        }
+    }
+
+    pub fn apply_binding_profile(&mut self, profile: BindingProfile) {
+        self.accessibility.settings = profile.accessibility;
+        self.accessibility.states.clear();
+        self.accessibility.latched_modifiers.clear();
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub(super) struct ActionState {
+    pub pressed: bool,
+    pub just_pressed: bool,
+    pub just_released: bool,
+    down_last_frame: bool,
+    held_for: f32,
+    repeats: u32,
+}
+
+impl ActionState {
+    fn update(&mut self, down: bool, toggle: bool, repeat: Option<RepeatOnHold>, dt: f32) {
+        let was_pressed = self.pressed;
+        if toggle {
+            if down && !self.down_last_frame {
+                self.pressed = !self.pressed;
+            }
+        } else {
+            self.pressed = down;
+        }
+        self.down_last_frame = down;
+
+        self.just_pressed = self.pressed && !was_pressed;
+        self.just_released = !self.pressed && was_pressed;
+
+        if self.just_pressed || !self.pressed {
+            self.held_for = 0.;
+            self.repeats = 0;
+        } else if let Some(repeat) = repeat {
+            self.held_for += dt;
+            let next_repeat = repeat.delay + self.repeats as f32 / repeat.rate.max(f32::EPSILON);
+            if self.held_for >= next_repeat {
+                self.just_pressed = true;
+                self.repeats += 1;
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub(super) struct Layer {
+    pub settings: Settings,
+    // as last loaded or written, the settings are saved whenever they differ
+    saved_settings: Settings,
+    states: HashMap<Action, ActionState>,
+    latched_modifiers: HashSet<button::Variant>,
+    scan_index: usize,
+    scan_elapsed: f32,
+    scan_focus: Option<Action>,
+}
+
+impl Layer {
+    fn has_unsaved_changes(&self) -> bool {
+        self.settings != self.saved_settings
+    }
+
+    pub fn state(&self, action: Action) -> Option<&ActionState> {
+        self.states.get(&action)
+    }
+
+    pub fn scan_focus(&self) -> Option<Action> {
+        self.scan_focus
+    }
+
+    fn update_scanning(
+        &mut self,
+        actions: &[Action],
+        held_buttons: &HashSet<button::Variant>,
+        dt: f32,
+    ) -> bool {
+        let Some(scanning) = &self.settings.scanning else {
+            self.scan_focus = None;
+            return false;
+        };
+
+        let focusable = scanning
+            .actions
+            .iter()
+            .filter_map(|name| actions.iter().find(|a| a.0 == name).copied())
+            .collect::<Vec<_>>();
+        if focusable.is_empty() {
+            self.scan_focus = None;
+            return false;
+        }
+
+        let switch_down = held_buttons.contains(&scanning.switch);
+        if !switch_down {
+            self.scan_elapsed += dt;
+            if self.scan_elapsed >= scanning.interval {
+                self.scan_elapsed = 0.;
+                self.scan_index += 1;
+            }
+        }
+        self.scan_index %= focusable.len();
+        self.scan_focus = Some(focusable[self.scan_index]);
+        switch_down
+    }
+}
+
+pub(super) fn apply_accessibility(time: Res<Time>, mut input_manager: ResMut<InputManager>) {
+    let dt = time.delta_secs();
+    let InputManager {
+        button_entries,
+        chord_modifiers,
+        held_buttons,
+        frame_pressed_buttons,
+        accessibility,
+        ..
+    } = &mut *input_manager;
+
+    if accessibility.settings.sticky_modifiers {
+        for button in frame_pressed_buttons.iter() {
+            if chord_modifiers.values().any(|m| m.contains(button))
+                && !accessibility.latched_modifiers.remove(button)
+            {
+                accessibility.latched_modifiers.insert(*button);
+            }
+        }
+    } else {
+        accessibility.latched_modifiers.clear();
+    }
+
+    let actions = button_entries.keys().copied().collect::<Vec<_>>();
+    let switch_down = accessibility.update_scanning(&actions, held_buttons, dt);
+
+    let mut chord_consumed = false;
+    for (action, entry) in button_entries.iter() {
+        let modifiers = chord_modifiers.get(action);
+        if modifiers.is_none() && !accessibility.settings.is_layered(*action) {
+            accessibility.states.remove(action);
+            continue;
+        }
+
+        let modifiers_held = modifiers.is_none_or(|m| {
+            m.iter().all(|b| {
+                held_buttons.contains(b) || accessibility.latched_modifiers.contains(b)
+            })
+        });
+        let focused = switch_down && accessibility.scan_focus == Some(*action);
+        let down = focused
+            || (modifiers_held && (!entry.just_pressed.is_empty() || !entry.pressed.is_empty()));
+
+        let toggle = accessibility.settings.toggle_hold.contains(action.0);
+        let repeat = accessibility.settings.repeat_on_hold.get(action.0).copied();
+        let state = accessibility.states.entry(*action).or_default();
+        state.update(down, toggle, repeat, dt);
+
+        if modifiers.is_some() && state.just_pressed {
+            chord_consumed = true;
+        }
+    }
+
+    if chord_consumed {
+        accessibility.latched_modifiers.clear();
+    }
+}
+
+pub(super) fn load_binding_profile(mut input_manager: ResMut<InputManager>) {
+    if let Some(profile) = load_ron_file::<BindingProfile>(BINDING_PROFILE_PATH) {
+        input_manager.apply_binding_profile(profile);
+        let accessibility = &mut input_manager.accessibility;
+        accessibility.saved_settings = accessibility.settings.clone();
+    }
+}
+
+/**
+ * Writes the profile whenever the settings changed, and on exit
+ */
+pub(super) fn save_binding_profile(
+    ev_exit: EventReader<AppExit>,
+    mut input_manager: ResMut<InputManager>,
+) {
+    if !input_manager.accessibility.has_unsaved_changes() && ev_exit.is_empty() {
+        return;
+    }
+    let accessibility = &mut input_manager.accessibility;
+    accessibility.saved_settings = accessibility.settings.clone();
+    save_ron_file(BINDING_PROFILE_PATH, &input_manager.binding_profile());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    const JUMP: Action = Action("jump");
+    const DASH: Action = Action("dash");
+    const SHIFT: button::Variant = button::Variant::Keyboard(KeyCode::ShiftLeft);
+    const SPACE: button::Variant = button::Variant::Keyboard(KeyCode::Space);
+    const ENTER: button::Variant = button::Variant::Keyboard(KeyCode::Enter);
+
+    fn world(input_manager: InputManager) -> World {
+        let mut world = World::new();
+        world.insert_resource(Time::<()>::default());
+        world.insert_resource(input_manager);
+        world
+    }
+
+    /**
+     * Runs one frame the way read_button_input and apply_accessibility would
+     */
+    fn frame(
+        world: &mut World,
+        pressed: &[button::Variant],
+        released: &[button::Variant],
+        dt: f32,
+    ) {
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(dt));
+        {
+            let mut input_manager = world.resource_mut::<InputManager>();
+            input_manager.move_prev_frame_just_pressed();
+            input_manager.move_prev_frame_just_released();
+            input_manager.frame_pressed_buttons.clear();
+            for button in pressed {
+                input_manager.set_button_pressed(*button);
+            }
+            for button in released {
+                input_manager.set_button_released(*button);
+            }
+        }
+        world.run_system_once(apply_accessibility).unwrap();
+    }
+
+    #[test]
+    fn toggle_hold_flips_on_each_press() {
+        let mut state = ActionState::default();
+
+        state.update(true, true, None, 0.1);
+        assert!(state.pressed && state.just_pressed);
+
+        // held down and let go, the action stays pressed
+        state.update(true, true, None, 0.1);
+        state.update(false, true, None, 0.1);
+        assert!(state.pressed && !state.just_pressed && !state.just_released);
+
+        state.update(true, true, None, 0.1);
+        assert!(!state.pressed && state.just_released);
+    }
+
+    #[test]
+    fn toggle_hold_through_input_manager() {
+        let mut input_manager = InputManager::default();
+        input_manager.register_action_button(JUMP, vec![SPACE]);
+        input_manager
+            .accessibility_mut()
+            .set_toggle_hold(JUMP, true);
+        let mut world = world(input_manager);
+
+        frame(&mut world, &[SPACE], &[], 0.1);
+        frame(&mut world, &[], &[SPACE], 0.1);
+        frame(&mut world, &[], &[], 0.1);
+        assert!(world.resource::<InputManager>().is_action_pressed(JUMP));
+
+        frame(&mut world, &[SPACE], &[], 0.1);
+        let input_manager = world.resource::<InputManager>();
+        assert!(!input_manager.is_action_pressed(JUMP));
+        assert!(input_manager.is_action_just_released(JUMP));
+    }
+
+    #[test]
+    fn sticky_modifier_latches_for_one_chord() {
+        let mut input_manager = InputManager::default();
+        input_manager.register_action_chord(DASH, vec![SHIFT], vec![SPACE]);
+        input_manager.accessibility_mut().sticky_modifiers = true;
+        let mut world = world(input_manager);
+
+        // tapping the modifier latches it
+        frame(&mut world, &[SHIFT], &[], 0.1);
+        frame(&mut world, &[], &[SHIFT], 0.1);
+        assert!(!world.resource::<InputManager>().is_action_pressed(DASH));
+
+        frame(&mut world, &[SPACE], &[], 0.1);
+        assert!(world
+            .resource::<InputManager>()
+            .is_action_just_pressed(DASH));
+
+        // the chord used up the latch
+        frame(&mut world, &[], &[], 0.1);
+        assert!(!world.resource::<InputManager>().is_action_pressed(DASH));
+    }
+
+    #[test]
+    fn sticky_modifier_tapped_twice_unlatches() {
+        let mut input_manager = InputManager::default();
+        input_manager.register_action_chord(DASH, vec![SHIFT], vec![SPACE]);
+        input_manager.accessibility_mut().sticky_modifiers = true;
+        let mut world = world(input_manager);
+
+        frame(&mut world, &[SHIFT], &[], 0.1);
+        frame(&mut world, &[], &[SHIFT], 0.1);
+        frame(&mut world, &[SHIFT], &[], 0.1);
+        frame(&mut world, &[], &[SHIFT], 0.1);
+
+        frame(&mut world, &[SPACE], &[], 0.1);
+        assert!(!world.resource::<InputManager>().is_action_pressed(DASH));
+    }
+
+    #[test]
+    fn scanning_cycles_focus_and_presses_it() {
+        let mut input_manager = InputManager::default();
+        input_manager.register_action_button(JUMP, vec![SPACE]);
+        input_manager.register_action_button(DASH, vec![SHIFT]);
+        input_manager
+            .accessibility_mut()
+            .set_scanning(ENTER, 1., &[JUMP, DASH]);
+        let mut world = world(input_manager);
+
+        frame(&mut world, &[], &[], 0.5);
+        assert_eq!(world.resource::<InputManager>().scan_focus(), Some(JUMP));
+
+        frame(&mut world, &[], &[], 0.6);
+        assert_eq!(world.resource::<InputManager>().scan_focus(), Some(DASH));
+
+        // wraps around to the first action
+        frame(&mut world, &[], &[], 1.);
+        assert_eq!(world.resource::<InputManager>().scan_focus(), Some(JUMP));
+
+        // holding the switch keeps the focus and presses it
+        frame(&mut world, &[ENTER], &[], 1.);
+        frame(&mut world, &[], &[], 1.);
+        let input_manager = world.resource::<InputManager>();
+        assert_eq!(input_manager.scan_focus(), Some(JUMP));
+        assert!(input_manager.is_action_pressed(JUMP));
+        assert!(!input_manager.is_action_pressed(DASH));
+    }
+
+    #[test]
+    fn only_changed_settings_are_marked_for_saving() {
+        let mut input_manager = InputManager::default();
+        assert!(!input_manager.accessibility.has_unsaved_changes());
+        input_manager.accessibility_mut();
+        assert!(!input_manager.accessibility.has_unsaved_changes());
+        input_manager.accessibility_mut().sticky_modifiers = true;
+        assert!(input_manager.accessibility.has_unsaved_changes());
+        input_manager.accessibility_mut().sticky_modifiers = false;
+        assert!(!input_manager.accessibility.has_unsaved_changes());
+    }
+}
@@ -1,46 +1,245 @@
 use bevy::{
-    prelude::*, utils::HashMap,
+    prelude::*, render::camera::CameraUpdateSystem, transform::TransformSystem, utils::HashMap,
 };
 
+use crate::{
+    easing::{lerp_angle, Easing},
+    ron_asset::RonAssetPlugin,
+};
+
+pub mod controls;
+pub mod editor;
+pub mod follow;
+pub mod limits;
+pub mod modes;
+pub mod occlusion;
+pub mod persistence;
+pub mod rail;
+pub mod screen;
+pub mod shake;
+pub mod snap;
+pub mod viewports;
+pub mod zoom;
+
 const UP: Dir3 = Dir3::Y;
 
-pub struct IsometricCameraPlugin;
+pub struct IsometricCameraPlugin {
+    // registers camera actions with default bindings and applies them in CameraMode::GAME
+    pub default_controls: bool,
+}
+
+impl Default for IsometricCameraPlugin {
+    fn default() -> Self {
+        Self {
+            default_controls: true,
+        }
+    }
+}
+
 impl Plugin for IsometricCameraPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<CameraManager>()
-            .add_systems(Startup, setup)
-            .add_systems(Update, update);
+        if self.default_controls {
+            app.init_resource::<controls::CameraControlSettings>()
+                .add_systems(Startup, controls::register_input)
+                .add_systems(Update, controls::apply_input.before(rail::start_rails));
+        }
+        app.add_plugins(RonAssetPlugin::<rail::CameraRail>::new(&["rail.ron"]))
+            .init_resource::<CameraManager>()
+            .add_event::<CameraTransitionStarted>()
+            .add_event::<CameraTransitionEnded>()
+            .add_event::<rail::PlayCameraRail>()
+            .add_event::<rail::CameraRailKeyframe>()
+            .add_event::<rail::CameraRailFinished>()
+            .add_systems(
+                Startup,
+                (
+                    setup,
+                    editor::register_input,
+                    persistence::register_input,
+                    rail::register_mode,
+                ),
+            )
+            .add_systems(PostStartup, persistence::load_camera_state)
+            .add_systems(
+                Update,
+                (
+                    rail::start_rails,
+                    persistence::editor_bookmarks,
+                    editor::editor_camera_control,
+                    follow::follow_target,
+                    occlusion::update_occlusion,
+                    viewports::sync_cameras,
+                    update,
+                    rail::finish_rails,
+                    viewports::update_viewports,
+                    occlusion::fade_occluders,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                PostUpdate,
+                screen::cache_views
+                    .after(TransformSystem::TransformPropagate)
+                    .after(CameraUpdateSystem),
+            )
+            .add_systems(
+                Last,
+                persistence::save_camera_state.after(crate::exit_game::ExitGameSystem),
+            );
     }
 }
 
+/**
+ * Key for a registered camera mode, plugins can declare their own
+ * like `CameraMode("cutscene")` and register them on the CameraManager
+ */
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-pub enum CameraMode {
-    Game,
-    Editor,
+pub struct CameraMode(pub &'static str);
+
+impl CameraMode {
+    pub const GAME: Self = Self("game");
+    pub const EDITOR: Self = Self("editor");
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CameraTransitionStarted {
+    pub from: CameraMode,
+    pub to: CameraMode,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CameraTransitionEnded {
+    pub mode: CameraMode,
+    // true if another set_mode call took over before the blend finished
+    pub interrupted: bool,
+}
+
+#[derive(Debug)]
+enum TransitionEvent {
+    Started(CameraTransitionStarted),
+    Ended(CameraTransitionEnded),
+}
+
+#[derive(Debug)]
+struct Transition {
+    from: IsometricCamera,
+    elapsed: f32,
 }
 
 #[derive(Debug, Resource)]
 pub struct CameraManager {
     current_mode: CameraMode,
     cameras: HashMap<CameraMode, IsometricCamera>,
+    controllers: modes::Controllers,
+    mode_stack: Vec<CameraMode>,
+    transition: Option<Transition>,
+    transition_duration: f32,
+    transition_easing: Easing,
+    transition_events: Vec<TransitionEvent>,
+    follow: HashMap<CameraMode, follow::CameraFollow>,
+    occlusion_settings: occlusion::OcclusionSettings,
+    occlusion: occlusion::OcclusionState,
+    editor: editor::EditorCamera,
+    player_cameras: Vec<IsometricCamera>,
+    minimap: Option<viewports::MinimapSettings>,
+    shake: shake::ShakeState,
+    bookmarks: [Option<persistence::CameraPose>; 9],
+    // set when a bookmark is stored or the mode changes, saved at the end of the frame
+    state_changed: bool,
+    screen: screen::ScreenState,
 }
 
 impl Default for CameraManager {
     fn default() -> Self {
-        let default_mode = CameraMode::Game;
+        let default_mode = CameraMode::GAME;
         Self {
             current_mode: default_mode,
             cameras: HashMap::from([
-                (default_mode, IsometricCamera::default()),
-                (CameraMode::Editor, IsometricCamera::default()),
+                (
+                    default_mode,
+                    IsometricCamera {
+                        yaw_snap: Some(snap::YawSnap::default()),
+                        ..default()
+                    },
+                ),
+                (CameraMode::EDITOR, IsometricCamera::default()),
             ]),
+            controllers: modes::Controllers::default(),
+            mode_stack: Vec::new(),
+            transition: None,
+            transition_duration: 0.6,
+            transition_easing: Easing::default(),
+            transition_events: Vec::new(),
+            follow: HashMap::default(),
+            occlusion_settings: occlusion::OcclusionSettings::default(),
+            occlusion: occlusion::OcclusionState::default(),
+            editor: editor::EditorCamera::default(),
+            player_cameras: Vec::new(),
+            minimap: None,
+            shake: shake::ShakeState::default(),
+            bookmarks: [None; 9],
+            state_changed: false,
+            screen: screen::ScreenState::default(),
         }
     }
 }
 
 impl CameraManager {
     fn get_camera_transform(&self) -> Transform {
-        self.get().get_camera_transform()
+        let mut pose = self.current_pose();
+        if let Some(arm_length) = self.occlusion.arm_length {
+            pose.spring_arm_length = pose.spring_arm_length.min(arm_length);
+        }
+        self.shake.apply(pose.get_camera_transform())
+    }
+
+    /**
+     * The rig as currently seen, which is a blend of the previous pose
+     * and the active mode's rig while a transition is running
+     */
+    fn current_pose(&self) -> IsometricCamera {
+        match &self.transition {
+            Some(transition) => {
+                let t = self.transition_easing.sample(self.transition_progress(transition));
+                transition.from.lerp(self.get(), t)
+            }
+            None => self.get().clone(),
+        }
+    }
+
+    fn transition_progress(&self, transition: &Transition) -> f32 {
+        if self.transition_duration <= 0. {
+            return 1.;
+        }
+        (transition.elapsed / self.transition_duration).min(1.)
+    }
+
+    fn advance_transition(&mut self, delta: f32) {
+        let Some(transition) = &mut self.transition else {
+            return;
+        };
+        transition.elapsed += delta;
+        if transition.elapsed >= self.transition_duration {
+            self.transition = None;
+            self.transition_events
+                .push(TransitionEvent::Ended(CameraTransitionEnded {
+                    mode: self.current_mode,
+                    interrupted: false,
+                }));
+        }
+    }
+
+    pub fn get_mode(&self) -> CameraMode {
+        self.current_mode
+    }
+
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    pub fn set_transition_settings(&mut self, duration: f32, easing: Easing) {
+        self.transition_duration = duration;
+        self.transition_easing = easing;
     }
 
     fn get(&self) -> &IsometricCamera {
@@ -51,9 +250,94 @@ impl CameraManager {
         self.cameras.get_mut(&self.current_mode).unwrap()
     }
 
-    pub fn set_mode(&mut self, mode: CameraMode) {
-        assert!(self.cameras.contains_key(&mode));
-        self.current_mode = mode
+    /**
+     * Blends from the current pose to the rig of `mode`.
+     * Switching to another mode mid-blend starts a new blend from wherever the
+     * camera is, switching to the current one lets the blend finish.
+     * Returns false, leaving the camera as is, if `mode` isn't registered
+     */
+    pub fn set_mode(&mut self, mode: CameraMode) -> bool {
+        if !self.check_registered(mode) {
+            return false;
+        }
+        if mode == self.current_mode {
+            return true;
+        }
+
+        self.begin_transition(mode);
+        self.switch_controller(self.current_mode, mode);
+        self.current_mode = mode;
+        self.state_changed = true;
+        true
+    }
+
+    fn check_registered(&self, mode: CameraMode) -> bool {
+        let registered = self.cameras.contains_key(&mode);
+        if !registered {
+            warn!("Camera mode not registered: {}", mode.0);
+        }
+        registered
+    }
+
+    /**
+     * Rig of `mode` for per-mode settings, warns if it isn't registered
+     */
+    fn rig_mut(&mut self, mode: CameraMode) -> Option<&mut IsometricCamera> {
+        if !self.check_registered(mode) {
+            return None;
+        }
+        self.cameras.get_mut(&mode)
+    }
+
+    fn begin_transition(&mut self, to: CameraMode) {
+        let from = self.current_pose();
+        self.interrupt_transition();
+        self.transition_events
+            .push(TransitionEvent::Started(CameraTransitionStarted {
+                from: self.current_mode,
+                to,
+            }));
+        self.transition = Some(Transition { from, elapsed: 0. });
+    }
+
+    fn interrupt_transition(&mut self) {
+        if self.transition.take().is_some() {
+            self.transition_events
+                .push(TransitionEvent::Ended(CameraTransitionEnded {
+                    mode: self.current_mode,
+                    interrupted: true,
+                }));
+        }
+    }
+
+    /**
+     * Moves the active rig to a new pose, blending like a mode switch
+     */
+    pub fn set_pose_blended(
+        &mut self,
+        pivot: Vec3,
+        angle_yaw: f32,
+        angle_pitch: f32,
+        spring_arm_length: f32,
+    ) {
+        self.begin_transition(self.current_mode);
+        self.get_mut()
+            .set_pose(pivot, angle_yaw, angle_pitch, spring_arm_length);
+    }
+
+    /**
+     * Cuts to the rig of `mode`, ending a running blend as interrupted.
+     * Returns false if `mode` isn't registered
+     */
+    pub fn set_mode_immediate(&mut self, mode: CameraMode) -> bool {
+        if !self.check_registered(mode) {
+            return false;
+        }
+        self.interrupt_transition();
+        self.switch_controller(self.current_mode, mode);
+        self.state_changed |= mode != self.current_mode;
+        self.current_mode = mode;
+        true
     }
 
     pub fn move_camera_global(&mut self, movement: Vec3) {
@@ -71,14 +355,30 @@ impl CameraManager {
     pub fn rotate_camera_pitch(&mut self, rotation: f32) {
         self.get_mut().rotate_camera_pitch(rotation)
     }
+
+    /**
+     * Rotates a movement given in view space, like get_motion3z, into world
+     * space using the yaw of the active view
+     */
+    pub fn view_to_world(&self, movement: Vec3) -> Vec3 {
+        let quat = Quat::from_rotation_y(self.current_pose().angle_yaw.to_radians());
+        quat.mul_vec3(movement)
+    }
 }
 
-#[derive(Debug, Component)]
+#[derive(Debug, Clone, Component)]
 pub struct IsometricCamera {
     pivot: Vec3,
     angle_yaw: f32,
     angle_pitch: f32,
     spring_arm_length: f32,
+    ortho_height: f32,
+    projection: zoom::ProjectionKind,
+    zoom: zoom::ZoomSettings,
+    zoom_target: Option<f32>,
+    yaw_snap: Option<snap::YawSnap>,
+    snap_state: snap::SnapState,
+    limits: limits::CameraLimits,
 }
 
 impl Default for IsometricCamera {
@@ -88,27 +388,93 @@ impl Default for IsometricCamera {
             angle_yaw: 45., // angles in degrees
             angle_pitch: -45.,
             spring_arm_length: 20.,
+            ortho_height: 20.,
+            projection: zoom::ProjectionKind::default(),
+            zoom: zoom::ZoomSettings::default(),
+            zoom_target: None,
+            yaw_snap: None,
+            snap_state: snap::SnapState::default(),
+            limits: limits::CameraLimits::default(),
         }
     }
 }
 
 impl IsometricCamera {
-    fn move_camera_global(&mut self, movement: Vec3) {
-        self.pivot += movement
+    /**
+     * Interpolates the pose, settings are taken from `other`
+     */
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            pivot: self.pivot.lerp(other.pivot, t),
+            angle_yaw: lerp_angle(self.angle_yaw, other.angle_yaw, t),
+            angle_pitch: self.angle_pitch + (other.angle_pitch - self.angle_pitch) * t,
+            spring_arm_length: self.spring_arm_length
+                + (other.spring_arm_length - self.spring_arm_length) * t,
+            ortho_height: self.ortho_height + (other.ortho_height - self.ortho_height) * t,
+            ..other.clone()
+        }
     }
 
-    fn move_camera_local(&mut self, movement: Vec3) {
+    pub fn new(pivot: Vec3, angle_yaw: f32, angle_pitch: f32, spring_arm_length: f32) -> Self {
+        Self {
+            pivot,
+            angle_yaw,
+            angle_pitch,
+            spring_arm_length,
+            ..default()
+        }
+    }
+
+    pub fn pivot(&self) -> Vec3 {
+        self.pivot
+    }
+
+    pub fn angle_yaw(&self) -> f32 {
+        self.angle_yaw
+    }
+
+    pub fn angle_pitch(&self) -> f32 {
+        self.angle_pitch
+    }
+
+    pub fn spring_arm_length(&self) -> f32 {
+        self.spring_arm_length
+    }
+
+    pub fn set_pose(
+        &mut self,
+        pivot: Vec3,
+        angle_yaw: f32,
+        angle_pitch: f32,
+        spring_arm_length: f32,
+    ) {
+        self.pivot = pivot;
+        self.angle_yaw = angle_yaw;
+        self.angle_pitch = angle_pitch;
+        self.spring_arm_length = spring_arm_length;
+        self.zoom_target = None;
+        self.constrain();
+    }
+
+    pub fn move_camera_global(&mut self, movement: Vec3) {
+        self.pivot = self.resist_pivot(movement)
+    }
+
+    pub fn move_camera_local(&mut self, movement: Vec3) {
         let quat = Quat::from_rotation_y(self.angle_yaw.to_radians());
-        self.pivot += quat.mul_vec3(movement)
+        self.pivot = self.resist_pivot(quat.mul_vec3(movement))
     }
 
-    fn rotate_camera_yaw(&mut self, rotation: f32) {
-        self.angle_yaw += rotation
+    pub fn rotate_camera_yaw(&mut self, rotation: f32) {
+        if self.yaw_snap.is_some() {
+            self.queue_yaw_step(rotation);
+            return;
+        }
+        self.angle_yaw = self.resist_yaw(rotation)
     }
 
-    fn rotate_camera_pitch(&mut self, rotation: f32) {
-        self.angle_pitch -= rotation;
-        self.angle_pitch = self.angle_pitch.clamp(-89., 89.)
+    pub fn rotate_camera_pitch(&mut self, rotation: f32) {
+        self.angle_pitch = self.resist_pitch(-rotation)
     }
 
     fn get_camera_transform(&self) -> Transform {
@@ -132,13 +498,54 @@ impl IsometricCamera {
 }
 
 fn setup(mut commands: Commands, camera_manager: Res<CameraManager>) {
-    commands.spawn((Camera3d::default(), camera_manager.get_camera_transform()));
+    viewports::spawn_managed_camera(
+        &mut commands,
+        &camera_manager,
+        viewports::CameraRole::Player(0),
+    );
     println!(
         "camera transform: {:?}",
         camera_manager.get_camera_transform()
     );
 }
 
-fn update(camera_manager: Res<CameraManager>, mut transform: Single<&mut Transform, With<Camera>>) {
-    **transform = camera_manager.get_camera_transform();
+fn update(
+    time: Res<Time>,
+    mut camera_manager: ResMut<CameraManager>,
+    mut cameras: Query<(&viewports::ManagedCamera, &mut Transform, &mut Projection)>,
+    mut ev_started: EventWriter<CameraTransitionStarted>,
+    mut ev_ended: EventWriter<CameraTransitionEnded>,
+) {
+    camera_manager.advance_transition(time.delta_secs());
+    camera_manager.update_controller(time.delta_secs());
+    camera_manager.shake.advance(time.delta_secs());
+    let CameraManager {
+        cameras: rigs,
+        player_cameras,
+        ..
+    } = &mut *camera_manager;
+    for rig in rigs.values_mut().chain(player_cameras.iter_mut()) {
+        rig.update_zoom(time.delta_secs());
+        rig.update_yaw_snap(time.delta_secs());
+    }
+    for event in camera_manager.transition_events.drain(..) {
+        match event {
+            TransitionEvent::Started(event) => {
+                ev_started.send(event);
+            }
+            TransitionEvent::Ended(event) => {
+                ev_ended.send(event);
+            }
+        }
+    }
+    for (managed, mut transform, mut projection) in &mut cameras {
+        let (Some(role_transform), Some(role_projection)) = (
+            camera_manager.role_transform(managed.role),
+            camera_manager.role_projection(managed.role),
+        ) else {
+            continue;
+        };
+        *transform = role_transform;
+        zoom::apply_projection(&mut projection, role_projection);
+    }
 }
@@ -0,0 +1,174 @@
+use bevy::{
+    input::mouse::{AccumulatedMouseScroll, MouseScrollUnit},
+    prelude::*,
+};
+
+use super::{CameraManager, CameraMode};
+use crate::input_manager::{self as input, button, motion, InputType};
+
+pub static CAMERA_MOVE: input::Action = input::Action("camera_move");
+pub static CAMERA_LOOK: input::Action = input::Action("camera_look");
+pub static CAMERA_LOOK_MOUSE: input::Action = input::Action("camera_look_mouse");
+pub static CAMERA_LOOK_HOLD: input::Action = input::Action("camera_look_hold");
+pub static CAMERA_ZOOM_IN: input::Action = input::Action("camera_zoom_in");
+pub static CAMERA_ZOOM_OUT: input::Action = input::Action("camera_zoom_out");
+
+/**
+ * Tuning for the built-in game camera controls. Keys and sticks are
+ * rates scaled by frame time, mouse motion is a per-frame distance
+ */
+#[derive(Debug, Clone, Resource)]
+pub struct CameraControlSettings {
+    pub enabled: bool,
+    // units per second at full input
+    pub move_speed: f32,
+    // degrees per second at full input
+    pub yaw_speed: f32,
+    pub pitch_speed: f32,
+    // degrees per pixel of mouse motion
+    pub mouse_sensitivity: f32,
+    // mouse motion only looks around while CAMERA_LOOK_HOLD is held
+    pub mouse_look_hold: bool,
+    pub invert_pitch: bool,
+    // zoom units per second while a zoom button is held
+    pub zoom_speed: f32,
+    // zoom units per mouse wheel notch
+    pub wheel_zoom_step: f32,
+    // pixels of touchpad or smooth scrolling counted as one notch
+    pub scroll_pixels_per_notch: f32,
+}
+
+impl Default for CameraControlSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            move_speed: 10.,
+            yaw_speed: 120.,
+            pitch_speed: 90.,
+            mouse_sensitivity: 0.1,
+            mouse_look_hold: true,
+            invert_pitch: false,
+            zoom_speed: 20.,
+            wheel_zoom_step: 2.,
+            scroll_pixels_per_notch: 16.,
+        }
+    }
+}
+
+pub(super) fn register_input(mut im: ResMut<input::InputManager>) {
+    im.register_action_motion(
+        CAMERA_MOVE,
+        vec![
+            motion::Entry {
+                input_type: InputType::Keyboard,
+                relations: vec![
+                    motion::Relation::KeyCode(KeyCode::KeyW, motion::Axis::PosY),
+                    motion::Relation::KeyCode(KeyCode::KeyS, motion::Axis::NegY),
+                    motion::Relation::KeyCode(KeyCode::KeyD, motion::Axis::PosX),
+                    motion::Relation::KeyCode(KeyCode::KeyA, motion::Axis::NegX),
+                ],
+            },
+            motion::Entry {
+                input_type: InputType::Gamepad,
+                relations: vec![
+                    motion::Relation::GamepadAxis(GamepadAxis::LeftStickY, motion::Axis::Y),
+                    motion::Relation::GamepadAxis(GamepadAxis::LeftStickX, motion::Axis::X),
+                ],
+            },
+        ],
+    );
+    im.register_action_motion(
+        CAMERA_LOOK,
+        vec![
+            motion::Entry {
+                input_type: InputType::Keyboard,
+                relations: vec![
+                    motion::Relation::KeyCode(KeyCode::KeyK, motion::Axis::PosY),
+                    motion::Relation::KeyCode(KeyCode::KeyJ, motion::Axis::NegY),
+                    motion::Relation::KeyCode(KeyCode::KeyL, motion::Axis::PosX),
+                    motion::Relation::KeyCode(KeyCode::KeyH, motion::Axis::NegX),
+                ],
+            },
+            motion::Entry {
+                input_type: InputType::Gamepad,
+                relations: vec![
+                    motion::Relation::GamepadAxis(GamepadAxis::RightStickY, motion::Axis::Y),
+                    motion::Relation::GamepadAxis(GamepadAxis::RightStickX, motion::Axis::X),
+                ],
+            },
+        ],
+    );
+    im.register_action_motion(
+        CAMERA_LOOK_MOUSE,
+        vec![motion::Entry {
+            input_type: InputType::Mouse,
+            relations: vec![motion::Relation::Mouse(1.)],
+        }],
+    );
+    im.register_action_button(
+        CAMERA_LOOK_HOLD,
+        vec![button::Variant::Mouse(MouseButton::Right)],
+    );
+    im.register_action_button(
+        CAMERA_ZOOM_IN,
+        vec![
+            button::Variant::Keyboard(KeyCode::Equal),
+            button::Variant::Gamepad(GamepadButton::RightTrigger),
+        ],
+    );
+    im.register_action_button(
+        CAMERA_ZOOM_OUT,
+        vec![
+            button::Variant::Keyboard(KeyCode::Minus),
+            button::Variant::Gamepad(GamepadButton::LeftTrigger),
+        ],
+    );
+}
+
+pub(super) fn apply_input(
+    time: Res<Time>,
+    settings: Res<CameraControlSettings>,
+    im: Res<input::InputManager>,
+    scroll: Res<AccumulatedMouseScroll>,
+    mut camera_manager: ResMut<CameraManager>,
+) {
+    if !settings.enabled || camera_manager.get_mode() != CameraMode::GAME {
+        return;
+    }
+    let delta = time.delta_secs();
+
+    // a followed rig has its pivot driven by the target instead
+    if !camera_manager.follow.contains_key(&CameraMode::GAME) {
+        let movement = im.get_motion3z(CAMERA_MOVE) * settings.move_speed * delta;
+        camera_manager.move_camera_local(movement);
+    }
+
+    let mouse = if !settings.mouse_look_hold || im.is_action_pressed(CAMERA_LOOK_HOLD) {
+        im.get_motion_raw(CAMERA_LOOK_MOUSE)
+    } else {
+        Vec2::ZERO
+    };
+    let look = im.get_motion(CAMERA_LOOK);
+    let yaw = look.x * settings.yaw_speed * delta + mouse.x * settings.mouse_sensitivity;
+    let mut pitch = look.y * settings.pitch_speed * delta + mouse.y * settings.mouse_sensitivity;
+    if settings.invert_pitch {
+        pitch = -pitch;
+    }
+    camera_manager.rotate_camera_yaw(yaw);
+    camera_manager.rotate_camera_pitch(pitch);
+
+    let notches = match scroll.unit {
+        MouseScrollUnit::Line => scroll.delta.y,
+        MouseScrollUnit::Pixel => scroll.delta.y / settings.scroll_pixels_per_notch.max(1.),
+    };
+    let mut zoom = notches * settings.wheel_zoom_step;
+    if im.is_action_pressed(CAMERA_ZOOM_IN) {
+        zoom += settings.zoom_speed * delta;
+    }
+    if im.is_action_pressed(CAMERA_ZOOM_OUT) {
+        zoom -= settings.zoom_speed * delta;
+    }
+    if zoom != 0. {
+        camera_manager.zoom(zoom);
+    }
+}
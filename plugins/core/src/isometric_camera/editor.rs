@@ -0,0 +1,278 @@
+use bevy::{
+    input::mouse::{AccumulatedMouseScroll, MouseScrollUnit},
+    prelude::*,
+    render::primitives::Aabb,
+};
+
+use super::{zoom::ProjectionKind, CameraManager, CameraMode, IsometricCamera, UP};
+use crate::input_manager::{self as input, button, motion, InputType};
+
+static EDITOR_MOVE: input::Action = input::Action("editor_camera_move");
+static EDITOR_UP: input::Action = input::Action("editor_camera_up");
+static EDITOR_DOWN: input::Action = input::Action("editor_camera_down");
+static EDITOR_LOOK: input::Action = input::Action("editor_camera_look");
+static EDITOR_LOOK_HOLD: input::Action = input::Action("editor_camera_look_hold");
+static EDITOR_FOCUS: input::Action = input::Action("editor_camera_focus");
+static EDITOR_CYCLE_STYLE: input::Action = input::Action("editor_camera_cycle_style");
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum EditorCameraStyle {
+    // orbit the pivot, which tracks the selected entity if there is one
+    #[default]
+    Orbit,
+    FreeFly,
+}
+
+/**
+ * Marks the entity the editor camera orbits around and frames on focus
+ */
+#[derive(Component)]
+pub struct EditorSelected;
+
+#[derive(Debug, Clone)]
+pub struct EditorCamera {
+    pub style: EditorCameraStyle,
+    // units per second
+    pub fly_speed: f32,
+    pub min_fly_speed: f32,
+    pub max_fly_speed: f32,
+    // fly speed multiplier per mouse wheel notch
+    pub wheel_speed_factor: f32,
+    // pixels of touchpad or smooth scrolling counted as one notch
+    pub scroll_pixels_per_notch: f32,
+    // degrees per pixel of mouse motion
+    pub look_sensitivity: f32,
+    pub pan_speed: f32,
+    // exponential smoothing rate for moving the pivot onto a focus point
+    pub focus_smoothing: f32,
+    focus_target: Option<Vec3>,
+}
+
+impl Default for EditorCamera {
+    fn default() -> Self {
+        Self {
+            style: EditorCameraStyle::default(),
+            fly_speed: 8.,
+            min_fly_speed: 0.5,
+            max_fly_speed: 100.,
+            wheel_speed_factor: 1.2,
+            scroll_pixels_per_notch: 16.,
+            look_sensitivity: 0.1,
+            pan_speed: 10.,
+            focus_smoothing: 8.,
+            focus_target: None,
+        }
+    }
+}
+
+impl CameraManager {
+    pub fn editor_camera(&self) -> &EditorCamera {
+        &self.editor
+    }
+
+    pub fn editor_camera_mut(&mut self) -> &mut EditorCamera {
+        &mut self.editor
+    }
+
+    pub fn set_editor_style(&mut self, style: EditorCameraStyle) {
+        self.editor.style = style;
+    }
+
+    /**
+     * False while the editor camera has the keyboard and mouse, so gameplay
+     * systems should ignore their actions
+     */
+    pub fn gameplay_input_enabled(&self) -> bool {
+        self.current_mode != CameraMode::EDITOR
+    }
+}
+
+impl IsometricCamera {
+    /**
+     * Rotates the view around the camera position instead of the pivot
+     */
+    fn rotate_around_eye(&mut self, yaw: f32, pitch: f32) {
+        let eye = self.get_camera_transform().translation;
+        self.angle_yaw += yaw;
+        self.rotate_camera_pitch(pitch);
+        let offset = self.get_camera_transform().translation - self.pivot;
+        self.pivot = eye - offset;
+    }
+
+    fn framing_distance(&self, radius: f32) -> f32 {
+        match self.projection {
+            ProjectionKind::Perspective => {
+                let fov = PerspectiveProjection::default().fov;
+                radius / (fov * 0.5).sin()
+            }
+            ProjectionKind::Orthographic => radius * 2.4,
+        }
+    }
+}
+
+pub(super) fn register_input(mut im: ResMut<input::InputManager>) {
+    im.register_action_motion(
+        EDITOR_MOVE,
+        vec![motion::Entry {
+            input_type: InputType::Keyboard,
+            relations: vec![
+                motion::Relation::KeyCode(KeyCode::KeyW, motion::Axis::PosY),
+                motion::Relation::KeyCode(KeyCode::KeyS, motion::Axis::NegY),
+                motion::Relation::KeyCode(KeyCode::KeyD, motion::Axis::PosX),
+                motion::Relation::KeyCode(KeyCode::KeyA, motion::Axis::NegX),
+            ],
+        }],
+    );
+    im.register_action_motion(
+        EDITOR_LOOK,
+        vec![motion::Entry {
+            input_type: InputType::Mouse,
+            relations: vec![motion::Relation::Mouse(1.)],
+        }],
+    );
+    im.register_action_button(EDITOR_UP, vec![button::Variant::Keyboard(KeyCode::PageUp)]);
+    im.register_action_button(
+        EDITOR_DOWN,
+        vec![button::Variant::Keyboard(KeyCode::PageDown)],
+    );
+    im.register_action_button(
+        EDITOR_LOOK_HOLD,
+        vec![button::Variant::Mouse(MouseButton::Right)],
+    );
+    im.register_action_button(EDITOR_FOCUS, vec![button::Variant::Keyboard(KeyCode::KeyF)]);
+    im.register_action_button(
+        EDITOR_CYCLE_STYLE,
+        vec![button::Variant::Keyboard(KeyCode::Tab)],
+    );
+}
+
+fn selection_bounds(
+    entity: Entity,
+    children: &Query<&Children>,
+    bounds: &Query<(&Aabb, &GlobalTransform)>,
+    transforms: &Query<&GlobalTransform>,
+) -> Option<(Vec3, f32)> {
+    let mut min = Vec3::splat(f32::MAX);
+    let mut max = Vec3::splat(f32::MIN);
+    for (aabb, transform) in std::iter::once(entity)
+        .chain(children.iter_descendants(entity))
+        .filter_map(|e| bounds.get(e).ok())
+    {
+        let center = transform.transform_point(aabb.center.into());
+        let radius = (transform.affine().matrix3 * aabb.half_extents).length();
+        min = min.min(center - Vec3::splat(radius));
+        max = max.max(center + Vec3::splat(radius));
+    }
+
+    if min.x > max.x {
+        // nothing with a mesh, frame the entity origin instead
+        return transforms.get(entity).ok().map(|t| (t.translation(), 1.));
+    }
+    Some(((min + max) * 0.5, (max - min).length() * 0.5))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn editor_camera_control(
+    time: Res<Time>,
+    im: Res<input::InputManager>,
+    scroll: Res<AccumulatedMouseScroll>,
+    mut camera_manager: ResMut<CameraManager>,
+    selected: Query<Entity, With<EditorSelected>>,
+    transforms: Query<&GlobalTransform>,
+    children: Query<&Children>,
+    bounds: Query<(&Aabb, &GlobalTransform)>,
+) {
+    if camera_manager.current_mode != CameraMode::EDITOR {
+        return;
+    }
+    let delta = time.delta_secs();
+    let CameraManager {
+        cameras, editor, ..
+    } = &mut *camera_manager;
+    let Some(rig) = cameras.get_mut(&CameraMode::EDITOR) else {
+        return;
+    };
+
+    if im.is_action_just_pressed(EDITOR_CYCLE_STYLE) {
+        editor.style = match editor.style {
+            EditorCameraStyle::Orbit => EditorCameraStyle::FreeFly,
+            EditorCameraStyle::FreeFly => EditorCameraStyle::Orbit,
+        };
+    }
+
+    let selection = selected.iter().next();
+    if im.is_action_just_pressed(EDITOR_FOCUS) {
+        if let Some((center, radius)) =
+            selection.and_then(|e| selection_bounds(e, &children, &bounds, &transforms))
+        {
+            editor.focus_target = Some(center);
+            rig.set_zoom_target(rig.framing_distance(radius));
+        }
+    }
+
+    let movement = im.get_motion(EDITOR_MOVE);
+    let mut vertical = 0.;
+    if im.is_action_pressed(EDITOR_UP) {
+        vertical += 1.;
+    }
+    if im.is_action_pressed(EDITOR_DOWN) {
+        vertical -= 1.;
+    }
+    let look = if im.is_action_pressed(EDITOR_LOOK_HOLD) {
+        im.get_motion_raw(EDITOR_LOOK) * editor.look_sensitivity
+    } else {
+        Vec2::ZERO
+    };
+    let notches = match scroll.unit {
+        MouseScrollUnit::Line => scroll.delta.y,
+        MouseScrollUnit::Pixel => scroll.delta.y / editor.scroll_pixels_per_notch.max(1.),
+    };
+
+    match editor.style {
+        EditorCameraStyle::FreeFly => {
+            if notches != 0. {
+                editor.fly_speed = (editor.fly_speed * editor.wheel_speed_factor.powf(notches))
+                    .clamp(editor.min_fly_speed, editor.max_fly_speed);
+            }
+            rig.rotate_around_eye(-look.x, look.y);
+
+            let transform = rig.get_camera_transform();
+            let direction =
+                transform.forward() * movement.y + transform.right() * movement.x + UP * vertical;
+            if direction != Vec3::ZERO {
+                editor.focus_target = None;
+                rig.pivot += direction * editor.fly_speed * delta;
+            }
+        }
+        EditorCameraStyle::Orbit => {
+            if notches != 0. {
+                let step = rig.zoom_value() * 0.1 * notches;
+                let from = rig.zoom_target.unwrap_or(rig.zoom_value());
+                rig.set_zoom_target(from - step);
+            }
+            rig.angle_yaw -= look.x;
+            rig.rotate_camera_pitch(look.y);
+
+            if movement != Vec2::ZERO || vertical != 0. {
+                editor.focus_target = None;
+                let pan = Vec3::new(-movement.x, vertical, movement.y) * editor.pan_speed * delta;
+                rig.move_camera_local(pan);
+            } else if let Some(selected) = selection.and_then(|e| transforms.get(e).ok()) {
+                if editor.focus_target.is_none() {
+                    rig.pivot = selected.translation();
+                }
+            }
+        }
+    }
+
+    if let Some(target) = editor.focus_target {
+        rig.pivot = rig
+            .pivot
+            .lerp(target, 1. - (-editor.focus_smoothing * delta).exp());
+        if rig.pivot.distance(target) < 0.01 {
+            rig.pivot = target;
+            editor.focus_target = None;
+        }
+    }
+    rig.constrain();
+}
@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+
+use super::{limits::PivotBounds, CameraManager, CameraMode};
+
+#[derive(Debug, Clone)]
+pub struct CameraFollow {
+    pub target: Entity,
+    pub offset: Vec3,
+    // exponential smoothing rate per second, higher is snappier
+    pub damping: f32,
+    // half extents of the box around the pivot the target can move in freely
+    pub dead_zone: Vec3,
+    // distance the pivot leads the target in its horizontal movement direction
+    pub look_ahead: f32,
+    pub look_ahead_damping: f32,
+    pub bounds: Option<PivotBounds>,
+
+    last_target_position: Option<Vec3>,
+    look_ahead_offset: Vec3,
+}
+
+impl CameraFollow {
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target,
+            offset: Vec3::ZERO,
+            damping: 6.,
+            dead_zone: Vec3::new(0.5, 0.5, 0.5),
+            look_ahead: 1.5,
+            look_ahead_damping: 2.,
+            bounds: None,
+            last_target_position: None,
+            look_ahead_offset: Vec3::ZERO,
+        }
+    }
+
+    fn next_pivot(&mut self, pivot: Vec3, target_position: Vec3, delta: f32) -> Vec3 {
+        let target_position = target_position + self.offset;
+
+        let velocity = match self.last_target_position {
+            Some(last) if delta > 0. => (target_position - last) / delta,
+            _ => Vec3::ZERO,
+        };
+        self.last_target_position = Some(target_position);
+
+        let look_ahead_goal =
+            Vec3::new(velocity.x, 0., velocity.z).normalize_or_zero() * self.look_ahead;
+        self.look_ahead_offset = self
+            .look_ahead_offset
+            .lerp(look_ahead_goal, smoothing(self.look_ahead_damping, delta));
+
+        let desired = target_position + self.look_ahead_offset;
+        let offset = desired - pivot;
+        let excess = Vec3::new(
+            excess_outside(offset.x, self.dead_zone.x),
+            excess_outside(offset.y, self.dead_zone.y),
+            excess_outside(offset.z, self.dead_zone.z),
+        );
+
+        let next = pivot.lerp(pivot + excess, smoothing(self.damping, delta));
+        match &self.bounds {
+            Some(bounds) => bounds.clamp(next),
+            None => next,
+        }
+    }
+}
+
+fn excess_outside(value: f32, half_extent: f32) -> f32 {
+    if value > half_extent {
+        value - half_extent
+    } else if value < -half_extent {
+        value + half_extent
+    } else {
+        0.
+    }
+}
+
+// frame-rate independent lerp factor
+fn smoothing(rate: f32, delta: f32) -> f32 {
+    1. - (-rate * delta).exp()
+}
+
+impl CameraManager {
+    /**
+     * Makes the rig of `mode` follow an entity, replacing any previous target
+     */
+    pub fn set_follow(&mut self, mode: CameraMode, follow: Option<CameraFollow>) {
+        match follow {
+            Some(follow) => self.follow.insert(mode, follow),
+            None => self.follow.remove(&mode),
+        };
+    }
+
+    pub fn get_follow_mut(&mut self, mode: CameraMode) -> Option<&mut CameraFollow> {
+        self.follow.get_mut(&mode)
+    }
+}
+
+pub(super) fn follow_target(
+    time: Res<Time>,
+    mut camera_manager: ResMut<CameraManager>,
+    targets: Query<&GlobalTransform>,
+) {
+    let delta = time.delta_secs();
+    let CameraManager {
+        cameras, follow, ..
+    } = &mut *camera_manager;
+
+    for (mode, follow) in follow.iter_mut() {
+        let (Some(camera), Ok(target)) = (cameras.get_mut(mode), targets.get(follow.target)) else {
+            continue;
+        };
+        let pivot = follow.next_pivot(camera.pivot, target.translation(), delta);
+        camera.pivot = camera.limits.clamp_pivot(pivot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // no look-ahead and no smoothing, so the pivot moves straight to its goal
+    fn rigid_follow() -> CameraFollow {
+        CameraFollow {
+            damping: f32::INFINITY,
+            look_ahead: 0.,
+            ..CameraFollow::new(Entity::PLACEHOLDER)
+        }
+    }
+
+    #[test]
+    fn target_inside_the_dead_zone_leaves_the_pivot() {
+        let mut follow = rigid_follow();
+        let pivot = follow.next_pivot(Vec3::ZERO, Vec3::new(0.4, -0.4, 0.2), 0.1);
+        assert_eq!(pivot, Vec3::ZERO);
+    }
+
+    #[test]
+    fn pivot_trails_the_target_by_the_dead_zone() {
+        let mut follow = rigid_follow();
+        let pivot = follow.next_pivot(Vec3::ZERO, Vec3::new(3., 0., -2.), 0.1);
+        assert_eq!(pivot, Vec3::new(2.5, 0., -1.5));
+    }
+
+    #[test]
+    fn damping_eases_towards_the_target() {
+        let mut follow = CameraFollow {
+            damping: 1.,
+            ..rigid_follow()
+        };
+        let pivot = follow.next_pivot(Vec3::ZERO, Vec3::new(10.5, 0., 0.), 0.1);
+        assert!(pivot.x > 0. && pivot.x < 1.);
+    }
+
+    #[test]
+    fn pivot_is_clamped_into_the_bounds() {
+        let mut follow = CameraFollow {
+            bounds: Some(PivotBounds::Box {
+                min: Vec3::splat(-2.),
+                max: Vec3::splat(2.),
+            }),
+            ..rigid_follow()
+        };
+        let pivot = follow.next_pivot(Vec3::ZERO, Vec3::new(10., 0., -10.), 0.1);
+        assert_eq!(pivot, Vec3::new(2., 0., -2.));
+
+        follow.bounds = Some(PivotBounds::Sphere {
+            center: Vec3::ZERO,
+            radius: 1.,
+        });
+        let pivot = follow.next_pivot(Vec3::ZERO, Vec3::new(0., 0., 10.), 0.1);
+        assert_eq!(pivot, Vec3::new(0., 0., 1.));
+    }
+
+    #[test]
+    fn look_ahead_leads_the_movement_direction() {
+        let mut follow = CameraFollow {
+            look_ahead: 1.,
+            look_ahead_damping: f32::INFINITY,
+            dead_zone: Vec3::ZERO,
+            ..rigid_follow()
+        };
+        follow.next_pivot(Vec3::ZERO, Vec3::ZERO, 0.1);
+        let pivot = follow.next_pivot(Vec3::ZERO, Vec3::new(0., 0., 1.), 0.1);
+        assert_eq!(pivot, Vec3::new(0., 0., 2.));
+    }
+}
@@ -0,0 +1,268 @@
+use bevy::prelude::*;
+
+use super::{CameraManager, CameraMode, IsometricCamera};
+
+/**
+ * Closed range with its ends always in order, built with Limit::new
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limit {
+    min: f32,
+    max: f32,
+}
+
+impl Limit {
+    /**
+     * Swapped ends are put back in order, clamping would panic on them
+     */
+    pub fn new(min: f32, max: f32) -> Self {
+        Self {
+            min: min.min(max),
+            max: min.max(max),
+        }
+    }
+
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    pub fn contains(&self, value: f32) -> bool {
+        value >= self.min && value <= self.max
+    }
+
+    pub fn clamp(&self, value: f32) -> f32 {
+        value.clamp(self.min, self.max)
+    }
+}
+
+/**
+ * Region the pivot is kept inside of, e.g. the current sub-world cube.
+ * Used by the limits and by CameraFollow
+ */
+#[derive(Debug, Clone, Copy)]
+pub enum PivotBounds {
+    Box { min: Vec3, max: Vec3 },
+    Sphere { center: Vec3, radius: f32 },
+}
+
+impl PivotBounds {
+    pub fn clamp(&self, point: Vec3) -> Vec3 {
+        match *self {
+            PivotBounds::Box { min, max } => point.clamp(min, max),
+            PivotBounds::Sphere { center, radius } => {
+                center + (point - center).clamp_length_max(radius)
+            }
+        }
+    }
+
+    fn resist(&self, point: Vec3, movement: Vec3, soft_edge: f32) -> Vec3 {
+        match *self {
+            PivotBounds::Box { min, max } => Vec3::new(
+                resist(point.x, movement.x, Limit::new(min.x, max.x), soft_edge),
+                resist(point.y, movement.y, Limit::new(min.y, max.y), soft_edge),
+                resist(point.z, movement.z, Limit::new(min.z, max.z), soft_edge),
+            ),
+            PivotBounds::Sphere { center, radius } => {
+                // only the outward part of the movement is resisted
+                let offset = point - center;
+                let normal = offset.normalize_or_zero();
+                let outward = movement.dot(normal).max(0.);
+                let room = radius - offset.length();
+                let movement = movement - normal * outward * (1. - edge_factor(room, soft_edge));
+                self.clamp(point + movement)
+            }
+        }
+    }
+}
+
+/**
+ * Per-mode ranges for the rig, in degrees for angles and world units for the
+ * rest. Input heading towards a limit slows down over the last `soft_edge_*`
+ * before it, while poses set directly are clamped hard
+ */
+#[derive(Debug, Clone)]
+pub struct CameraLimits {
+    // None leaves yaw unbounded
+    pub yaw: Option<Limit>,
+    pub pitch: Limit,
+    // applied on top of the zoom limits
+    pub arm_length: Option<Limit>,
+    pub pivot: Option<PivotBounds>,
+    pub soft_edge_angle: f32,
+    pub soft_edge_distance: f32,
+}
+
+impl Default for CameraLimits {
+    fn default() -> Self {
+        Self {
+            yaw: None,
+            pitch: Limit::new(-89., 89.),
+            arm_length: None,
+            pivot: None,
+            soft_edge_angle: 0.,
+            soft_edge_distance: 0.,
+        }
+    }
+}
+
+impl CameraLimits {
+    pub(super) fn clamp_yaw(&self, yaw: f32) -> f32 {
+        self.yaw.map_or(yaw, |limit| limit.clamp(yaw))
+    }
+
+    pub(super) fn clamp_arm_length(&self, arm_length: f32) -> f32 {
+        self.arm_length
+            .map_or(arm_length, |limit| limit.clamp(arm_length))
+    }
+
+    pub(super) fn clamp_pivot(&self, pivot: Vec3) -> Vec3 {
+        self.pivot.map_or(pivot, |bounds| bounds.clamp(pivot))
+    }
+}
+
+// 1 away from the edge, falling to 0 at it
+fn edge_factor(room: f32, soft_edge: f32) -> f32 {
+    if soft_edge <= 0. {
+        return 1.;
+    }
+    (room / soft_edge).clamp(0., 1.)
+}
+
+fn resist(value: f32, delta: f32, limit: Limit, soft_edge: f32) -> f32 {
+    let room = if delta > 0. {
+        limit.max() - value
+    } else {
+        value - limit.min()
+    };
+    limit.clamp(value + delta * edge_factor(room, soft_edge))
+}
+
+impl IsometricCamera {
+    pub(super) fn resist_yaw(&self, rotation: f32) -> f32 {
+        match self.limits.yaw {
+            Some(limit) => resist(self.angle_yaw, rotation, limit, self.limits.soft_edge_angle),
+            None => self.angle_yaw + rotation,
+        }
+    }
+
+    pub(super) fn resist_pitch(&self, rotation: f32) -> f32 {
+        resist(
+            self.angle_pitch,
+            rotation,
+            self.limits.pitch,
+            self.limits.soft_edge_angle,
+        )
+    }
+
+    pub(super) fn resist_pivot(&self, movement: Vec3) -> Vec3 {
+        match &self.limits.pivot {
+            Some(bounds) => bounds.resist(self.pivot, movement, self.limits.soft_edge_distance),
+            None => self.pivot + movement,
+        }
+    }
+
+    /**
+     * Hard clamps the current pose into the limits
+     */
+    pub(super) fn constrain(&mut self) {
+        self.angle_yaw = self.limits.clamp_yaw(self.angle_yaw);
+        self.angle_pitch = self.limits.pitch.clamp(self.angle_pitch);
+        self.spring_arm_length = self.limits.clamp_arm_length(self.spring_arm_length);
+        self.pivot = self.limits.clamp_pivot(self.pivot);
+    }
+}
+
+impl CameraManager {
+    /**
+     * Returns false if `mode` isn't registered
+     */
+    pub fn set_limits(&mut self, mode: CameraMode, limits: CameraLimits) -> bool {
+        let Some(camera) = self.rig_mut(mode) else {
+            return false;
+        };
+        camera.limits = limits;
+        camera.zoom_target = None;
+        camera.constrain();
+        true
+    }
+
+    pub fn get_limits(&self, mode: CameraMode) -> Option<&CameraLimits> {
+        self.cameras.get(&mode).map(|camera| &camera.limits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swapped_ends_are_ordered() {
+        let limit = Limit::new(10., -10.);
+        assert_eq!((limit.min(), limit.max()), (-10., 10.));
+        assert_eq!(limit.clamp(20.), 10.);
+        assert_eq!(limit.clamp(-20.), -10.);
+        assert!(limit.contains(0.));
+        assert!(!limit.contains(10.5));
+    }
+
+    #[test]
+    fn movement_slows_down_over_the_soft_edge() {
+        let limit = Limit::new(-90., 90.);
+        // outside the soft edge the full movement applies
+        assert_eq!(resist(0., 5., limit, 10.), 5.);
+        // 5 of 10 degrees left, so half of it
+        assert_eq!(resist(85., 4., limit, 10.), 87.);
+        assert_eq!(resist(90., 5., limit, 10.), 90.);
+        // moving away from the edge isn't resisted
+        assert_eq!(resist(90., -5., limit, 10.), 85.);
+        // without a soft edge the limit is hard
+        assert_eq!(resist(88., 5., limit, 0.), 90.);
+    }
+
+    #[test]
+    fn sphere_bounds_resist_only_outward_movement() {
+        let bounds = PivotBounds::Sphere {
+            center: Vec3::ZERO,
+            radius: 10.,
+        };
+        let point = Vec3::new(9., 0., 0.);
+        // 1 of 2 units left, so half of it
+        let outward = bounds.resist(point, Vec3::new(2., 0., 0.), 2.);
+        assert_eq!(outward, Vec3::new(10., 0., 0.));
+        let sideways = bounds.resist(point, Vec3::new(0., 0., 3.), 2.);
+        assert_eq!(sideways, Vec3::new(9., 0., 3.));
+        let inward = bounds.resist(point, Vec3::new(-5., 0., 0.), 2.);
+        assert_eq!(inward, Vec3::new(4., 0., 0.));
+    }
+
+    #[test]
+    fn constrain_clamps_the_pose_hard() {
+        let mut rig = IsometricCamera {
+            pivot: Vec3::new(3., 0., -3.),
+            angle_yaw: 120.,
+            angle_pitch: -10.,
+            spring_arm_length: 30.,
+            limits: CameraLimits {
+                yaw: Some(Limit::new(0., 90.)),
+                pitch: Limit::new(-60., -20.),
+                arm_length: Some(Limit::new(5., 15.)),
+                pivot: Some(PivotBounds::Box {
+                    min: Vec3::splat(-1.),
+                    max: Vec3::splat(1.),
+                }),
+                soft_edge_angle: 10.,
+                soft_edge_distance: 1.,
+            },
+            ..Default::default()
+        };
+        rig.constrain();
+        assert_eq!(rig.angle_yaw, 90.);
+        assert_eq!(rig.angle_pitch, -20.);
+        assert_eq!(rig.spring_arm_length, 15.);
+        assert_eq!(rig.pivot, Vec3::new(1., 0., -1.));
+    }
+}
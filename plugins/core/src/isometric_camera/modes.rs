@@ -0,0 +1,137 @@
+use std::any::Any;
+
+use bevy::utils::HashMap;
+
+use super::{CameraManager, CameraMode, IsometricCamera};
+
+/**
+ * Logic for a plugin defined camera mode, run every frame while the mode is
+ * active. Controllers needing ECS data can be reached from the plugin's own
+ * systems through CameraManager::controller_mut
+ */
+pub trait CameraController: Any + Send + Sync {
+    fn enter(&mut self, _rig: &mut IsometricCamera) {}
+
+    fn update(&mut self, rig: &mut IsometricCamera, delta: f32);
+
+    fn exit(&mut self, _rig: &mut IsometricCamera) {}
+}
+
+#[derive(Default)]
+pub(super) struct Controllers(HashMap<CameraMode, Box<dyn CameraController>>);
+
+impl std::fmt::Debug for Controllers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+impl CameraManager {
+    /**
+     * Adds or replaces a camera mode with its own rig
+     */
+    pub fn register_mode(&mut self, mode: CameraMode, rig: IsometricCamera) {
+        self.replace_mode(mode, rig, None);
+    }
+
+    pub fn register_mode_with_controller(
+        &mut self,
+        mode: CameraMode,
+        rig: IsometricCamera,
+        controller: impl CameraController,
+    ) {
+        self.replace_mode(mode, rig, Some(Box::new(controller)));
+    }
+
+    /**
+     * Replacing the active mode exits its old controller and enters the new one
+     */
+    fn replace_mode(
+        &mut self,
+        mode: CameraMode,
+        rig: IsometricCamera,
+        controller: Option<Box<dyn CameraController>>,
+    ) {
+        let active = mode == self.current_mode;
+        if active {
+            self.exit_controller(mode);
+        }
+        self.cameras.insert(mode, rig);
+        match controller {
+            Some(controller) => self.controllers.0.insert(mode, controller),
+            None => self.controllers.0.remove(&mode),
+        };
+        if active {
+            self.enter_controller(mode);
+        }
+    }
+
+    pub fn is_registered(&self, mode: CameraMode) -> bool {
+        self.cameras.contains_key(&mode)
+    }
+
+    pub fn controller_mut<T: CameraController>(&mut self, mode: CameraMode) -> Option<&mut T> {
+        let controller: &mut dyn Any = self.controllers.0.get_mut(&mode)?.as_mut();
+        controller.downcast_mut::<T>()
+    }
+
+    /**
+     * Temporarily switches to `mode`, pop_mode returns to the current one.
+     * Pushes nothing if `mode` is already active, or returns false if it isn't registered
+     */
+    pub fn push_mode(&mut self, mode: CameraMode) -> bool {
+        let from = self.current_mode;
+        if mode == from {
+            return true;
+        }
+        if !self.set_mode(mode) {
+            return false;
+        }
+        self.mode_stack.push(from);
+        true
+    }
+
+    /**
+     * Returns to the mode active before the last push_mode, if any
+     */
+    pub fn pop_mode(&mut self) -> Option<CameraMode> {
+        let previous = self.mode_stack.pop()?;
+        self.set_mode(previous).then_some(previous)
+    }
+
+    pub(super) fn switch_controller(&mut self, from: CameraMode, to: CameraMode) {
+        if from == to {
+            return;
+        }
+        self.exit_controller(from);
+        self.enter_controller(to);
+    }
+
+    fn enter_controller(&mut self, mode: CameraMode) {
+        if let (Some(controller), Some(rig)) = (
+            self.controllers.0.get_mut(&mode),
+            self.cameras.get_mut(&mode),
+        ) {
+            controller.enter(rig);
+        }
+    }
+
+    fn exit_controller(&mut self, mode: CameraMode) {
+        if let (Some(controller), Some(rig)) = (
+            self.controllers.0.get_mut(&mode),
+            self.cameras.get_mut(&mode),
+        ) {
+            controller.exit(rig);
+        }
+    }
+
+    pub(super) fn update_controller(&mut self, delta: f32) {
+        let mode = self.current_mode;
+        if let (Some(controller), Some(rig)) = (
+            self.controllers.0.get_mut(&mode),
+            self.cameras.get_mut(&mode),
+        ) {
+            controller.update(rig, delta);
+        }
+    }
+}
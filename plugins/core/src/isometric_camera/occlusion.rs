@@ -0,0 +1,215 @@
+use bevy::{
+    picking::mesh_picking::ray_cast::{MeshRayCast, RayCastSettings},
+    prelude::*,
+};
+
+use super::CameraManager;
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum OcclusionMode {
+    Off,
+    // shorten the spring arm so the camera stays in front of geometry
+    #[default]
+    SpringArm,
+    // keep the arm length and fade out the meshes in the way instead
+    Fade,
+}
+
+#[derive(Debug, Clone)]
+pub struct OcclusionSettings {
+    pub mode: OcclusionMode,
+    // parallel rays are cast this far from the center ray, approximating a sphere cast
+    pub probe_radius: f32,
+    // distance kept between the camera and the hit surface
+    pub margin: f32,
+    pub min_arm_length: f32,
+    // units per second the arm extends back once unobstructed
+    pub recovery_speed: f32,
+    pub fade_alpha: f32,
+    // alpha change per second
+    pub fade_speed: f32,
+}
+
+impl Default for OcclusionSettings {
+    fn default() -> Self {
+        Self {
+            mode: OcclusionMode::default(),
+            probe_radius: 0.3,
+            margin: 0.2,
+            min_arm_length: 1.,
+            recovery_speed: 8.,
+            fade_alpha: 0.25,
+            fade_speed: 4.,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(super) struct OcclusionState {
+    // shortened arm length, None while the full arm is unobstructed
+    pub arm_length: Option<f32>,
+    pub occluders: Vec<Entity>,
+}
+
+/**
+ * Entities (and their children) the camera neither collides with nor fades
+ */
+#[derive(Component)]
+pub struct CameraCollisionIgnore;
+
+#[derive(Component)]
+pub struct FadedOccluder {
+    original: Handle<StandardMaterial>,
+    original_alpha: f32,
+    fade: f32,
+}
+
+impl CameraManager {
+    pub fn occlusion_settings(&self) -> &OcclusionSettings {
+        &self.occlusion_settings
+    }
+
+    pub fn occlusion_settings_mut(&mut self) -> &mut OcclusionSettings {
+        &mut self.occlusion_settings
+    }
+}
+
+pub(super) fn update_occlusion(
+    time: Res<Time>,
+    mut camera_manager: ResMut<CameraManager>,
+    mut ray_cast: MeshRayCast,
+    ignored: Query<(), With<CameraCollisionIgnore>>,
+    parents: Query<&Parent>,
+) {
+    let settings = camera_manager.occlusion_settings.clone();
+    if settings.mode == OcclusionMode::Off {
+        camera_manager.occlusion = OcclusionState::default();
+        return;
+    }
+
+    let pose = camera_manager.current_pose();
+    let desired = pose.get_camera_transform();
+    let to_camera = desired.translation - pose.pivot;
+    let arm = to_camera.length();
+    let Ok(direction) = Dir3::new(to_camera) else {
+        return;
+    };
+
+    let follow_target = camera_manager
+        .follow
+        .get(&camera_manager.current_mode)
+        .map(|f| f.target);
+    let filter = |entity: Entity| {
+        !std::iter::once(entity)
+            .chain(parents.iter_ancestors(entity))
+            .any(|e| Some(e) == follow_target || ignored.contains(e))
+    };
+    let never_early_exit = |_: Entity| false;
+    let ray_settings = RayCastSettings::default()
+        .with_filter(&filter)
+        .with_early_exit_test(&never_early_exit);
+
+    let right = desired.right() * settings.probe_radius;
+    let up = desired.up() * settings.probe_radius;
+    let mut nearest: Option<f32> = None;
+    let mut occluders = Vec::<Entity>::new();
+    for offset in [Vec3::ZERO, right, -right, up, -up] {
+        let ray = Ray3d {
+            origin: pose.pivot + offset,
+            direction,
+        };
+        for (entity, hit) in ray_cast.cast_ray(ray, &ray_settings) {
+            if hit.distance > arm {
+                break;
+            }
+            nearest = Some(nearest.map_or(hit.distance, |n| n.min(hit.distance)));
+            if !occluders.contains(entity) {
+                occluders.push(*entity);
+            }
+        }
+    }
+
+    let state = &mut camera_manager.occlusion;
+    match settings.mode {
+        OcclusionMode::SpringArm => {
+            let limit = nearest
+                .map(|d| (d - settings.margin).max(settings.min_arm_length))
+                .unwrap_or(arm)
+                .min(arm);
+            let current = state.arm_length.unwrap_or(arm);
+            let next = if limit < current {
+                limit
+            } else {
+                (current + settings.recovery_speed * time.delta_secs()).min(limit)
+            };
+            state.arm_length = (next < arm).then_some(next);
+            state.occluders.clear();
+        }
+        OcclusionMode::Fade => {
+            state.arm_length = None;
+            state.occluders = occluders;
+        }
+        OcclusionMode::Off => (),
+    }
+}
+
+pub(super) fn fade_occluders(
+    time: Res<Time>,
+    camera_manager: Res<CameraManager>,
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: Query<(
+        Entity,
+        &mut MeshMaterial3d<StandardMaterial>,
+        Option<&mut FadedOccluder>,
+    )>,
+) {
+    let occluders = &camera_manager.occlusion.occluders;
+    let settings = &camera_manager.occlusion_settings;
+
+    for entity in occluders {
+        let Ok((entity, mut material, None)) = meshes.get_mut(*entity) else {
+            continue;
+        };
+        let Some(mut faded) = materials.get(&material.0).cloned() else {
+            continue;
+        };
+        let original = material.0.clone();
+        let original_alpha = faded.base_color.alpha();
+        faded.alpha_mode = AlphaMode::Blend;
+        material.0 = materials.add(faded);
+        commands.entity(entity).insert(FadedOccluder {
+            original,
+            original_alpha,
+            fade: 1.,
+        });
+    }
+
+    let step = settings.fade_speed * time.delta_secs();
+    for (entity, mut material, faded) in &mut meshes {
+        let Some(mut faded) = faded else {
+            continue;
+        };
+
+        let goal = if occluders.contains(&entity) {
+            settings.fade_alpha
+        } else {
+            1.
+        };
+        faded.fade = if faded.fade > goal {
+            (faded.fade - step).max(goal)
+        } else {
+            (faded.fade + step).min(goal)
+        };
+
+        if faded.fade >= 1. && goal >= 1. {
+            materials.remove(&material.0);
+            material.0 = faded.original.clone();
+            commands.entity(entity).remove::<FadedOccluder>();
+        } else if let Some(copy) = materials.get_mut(&material.0) {
+            copy.base_color = copy
+                .base_color
+                .with_alpha(faded.original_alpha * faded.fade);
+        }
+    }
+}
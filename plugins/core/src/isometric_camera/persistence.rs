@@ -0,0 +1,181 @@
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{CameraManager, CameraMode, IsometricCamera};
+use crate::{
+    input_manager::{self as input, button},
+    ron_asset::{load_ron_file, save_ron_file},
+};
+
+pub const CAMERA_STATE_PATH: &str = "camera_state.ron";
+
+static BOOKMARK_SAVE_MODIFIER: input::Action = input::Action("camera_bookmark_save_modifier");
+static BOOKMARKS: [input::Action; 9] = [
+    input::Action("camera_bookmark_1"),
+    input::Action("camera_bookmark_2"),
+    input::Action("camera_bookmark_3"),
+    input::Action("camera_bookmark_4"),
+    input::Action("camera_bookmark_5"),
+    input::Action("camera_bookmark_6"),
+    input::Action("camera_bookmark_7"),
+    input::Action("camera_bookmark_8"),
+    input::Action("camera_bookmark_9"),
+];
+const BOOKMARK_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CameraPose {
+    pub pivot: Vec3,
+    pub angle_yaw: f32,
+    pub angle_pitch: f32,
+    pub spring_arm_length: f32,
+    pub ortho_height: f32,
+}
+
+impl From<&IsometricCamera> for CameraPose {
+    fn from(rig: &IsometricCamera) -> Self {
+        Self {
+            pivot: rig.pivot,
+            angle_yaw: rig.angle_yaw,
+            angle_pitch: rig.angle_pitch,
+            spring_arm_length: rig.spring_arm_length,
+            ortho_height: rig.ortho_height,
+        }
+    }
+}
+
+impl CameraPose {
+    fn apply(&self, rig: &mut IsometricCamera) {
+        rig.set_pose(
+            self.pivot,
+            self.angle_yaw,
+            self.angle_pitch,
+            self.spring_arm_length,
+        );
+        rig.ortho_height = self.ortho_height;
+    }
+}
+
+/**
+ * Camera state as written to disk, modes are stored by name
+ */
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraState {
+    pub modes: BTreeMap<String, CameraPose>,
+    pub bookmarks: [Option<CameraPose>; 9],
+}
+
+impl CameraManager {
+    pub fn camera_state(&self) -> CameraState {
+        CameraState {
+            modes: self
+                .cameras
+                .iter()
+                .map(|(mode, rig)| (mode.0.to_string(), CameraPose::from(rig)))
+                .collect(),
+            bookmarks: self.bookmarks,
+        }
+    }
+
+    /**
+     * Restores the rigs of registered modes, unknown mode names are ignored
+     */
+    pub fn apply_camera_state(&mut self, state: &CameraState) {
+        for (mode, rig) in self.cameras.iter_mut() {
+            if let Some(pose) = state.modes.get(mode.0) {
+                pose.apply(rig);
+            }
+        }
+        self.bookmarks = state.bookmarks;
+    }
+
+    pub fn save_bookmark(&mut self, slot: usize) {
+        let pose = CameraPose::from(self.get());
+        if let Some(bookmark) = self.bookmarks.get_mut(slot) {
+            *bookmark = Some(pose);
+            self.state_changed = true;
+        }
+    }
+
+    /**
+     * Blends the active rig to a saved bookmark, returns false if the slot is empty
+     */
+    pub fn jump_to_bookmark(&mut self, slot: usize) -> bool {
+        let Some(Some(pose)) = self.bookmarks.get(slot).copied() else {
+            return false;
+        };
+        self.set_pose_blended(
+            pose.pivot,
+            pose.angle_yaw,
+            pose.angle_pitch,
+            pose.spring_arm_length,
+        );
+        self.get_mut().ortho_height = pose.ortho_height;
+        true
+    }
+}
+
+pub(super) fn register_input(mut im: ResMut<input::InputManager>) {
+    im.register_action_button(
+        BOOKMARK_SAVE_MODIFIER,
+        vec![
+            button::Variant::Keyboard(KeyCode::ControlLeft),
+            button::Variant::Keyboard(KeyCode::ControlRight),
+        ],
+    );
+    for (action, key) in BOOKMARKS.iter().zip(BOOKMARK_KEYS) {
+        im.register_action_button(*action, vec![button::Variant::Keyboard(key)]);
+    }
+}
+
+pub(super) fn load_camera_state(mut camera_manager: ResMut<CameraManager>) {
+    if let Some(state) = load_ron_file::<CameraState>(CAMERA_STATE_PATH) {
+        camera_manager.apply_camera_state(&state);
+    }
+}
+
+/**
+ * Written when a bookmark is stored or the mode changes, and on exit
+ */
+pub(super) fn save_camera_state(
+    ev_exit: EventReader<AppExit>,
+    mut camera_manager: ResMut<CameraManager>,
+) {
+    if !camera_manager.state_changed && ev_exit.is_empty() {
+        return;
+    }
+    camera_manager.state_changed = false;
+    save_ron_file(CAMERA_STATE_PATH, &camera_manager.camera_state());
+}
+
+pub(super) fn editor_bookmarks(
+    im: Res<input::InputManager>,
+    mut camera_manager: ResMut<CameraManager>,
+) {
+    if camera_manager.get_mode() != CameraMode::EDITOR {
+        return;
+    }
+    let saving = im.is_action_pressed(BOOKMARK_SAVE_MODIFIER);
+    for (slot, action) in BOOKMARKS.iter().enumerate() {
+        if !im.is_action_just_pressed(*action) {
+            continue;
+        }
+        if saving {
+            camera_manager.save_bookmark(slot);
+        } else {
+            camera_manager.jump_to_bookmark(slot);
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use bevy::{asset::LoadState, prelude::*};
+use serde::Deserialize;
+
+use super::{modes::CameraController, CameraManager, CameraMode, IsometricCamera};
+use crate::easing::Easing;
+
+pub const RAIL: CameraMode = CameraMode("rail");
+
+/**
+ * Keyframed camera path, loaded from `*.rail.ron` files
+ */
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
+pub struct CameraRail {
+    pub keyframes: Vec<RailKeyframe>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RailKeyframe {
+    // seconds from the start of the rail
+    pub time: f32,
+    pub pivot: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub arm_length: f32,
+    // easing of the segment arriving at this keyframe
+    #[serde(default)]
+    pub easing: Easing,
+    // sent as a CameraRailKeyframe event when the keyframe is reached
+    #[serde(default)]
+    pub event: Option<String>,
+}
+
+impl CameraRail {
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0., |k| k.time)
+    }
+
+    /**
+     * Rewrites each yaw to be the shortest turn from the previous keyframe,
+     * so going from 350 to 10 degrees doesn't spin back through 180
+     */
+    fn unwrap_yaws(&mut self) {
+        let mut previous: Option<f32> = None;
+        for key in &mut self.keyframes {
+            if let Some(previous) = previous {
+                key.yaw = previous + (key.yaw - previous + 180.).rem_euclid(360.) - 180.;
+            }
+            previous = Some(key.yaw);
+        }
+    }
+
+    /**
+     * Catmull-Rom spline through the keyframes, returning the pivot
+     * and (yaw, pitch, arm length)
+     */
+    fn sample(&self, time: f32) -> Option<(Vec3, Vec3)> {
+        let keys = &self.keyframes;
+        let last = keys.len().checked_sub(1)?;
+        let segment = keys
+            .iter()
+            .rposition(|k| k.time <= time)
+            .unwrap_or(0)
+            .min(last.saturating_sub(1));
+        let next = (segment + 1).min(last);
+
+        let (k1, k2) = (&keys[segment], &keys[next]);
+        let span = k2.time - k1.time;
+        let t = if span > 0. {
+            k2.easing.sample((time - k1.time) / span)
+        } else {
+            1.
+        };
+        let k0 = &keys[segment.saturating_sub(1)];
+        let k3 = &keys[(next + 1).min(last)];
+
+        let angles = |k: &RailKeyframe| Vec3::new(k.yaw, k.pitch, k.arm_length);
+        Some((
+            catmull_rom(k0.pivot, k1.pivot, k2.pivot, k3.pivot, t),
+            catmull_rom(angles(k0), angles(k1), angles(k2), angles(k3), t),
+        ))
+    }
+}
+
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2. * p1
+        + (p2 - p0) * t
+        + (2. * p0 - 5. * p1 + 4. * p2 - p3) * t2
+        + (3. * p1 - p0 - 3. * p2 + p3) * t3)
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct PlayCameraRail(pub Handle<CameraRail>);
+
+#[derive(Event, Debug, Clone)]
+pub struct CameraRailKeyframe(pub String);
+
+#[derive(Event, Debug, Clone)]
+pub struct CameraRailFinished {
+    pub rail: Handle<CameraRail>,
+    // true if another rail was started before this one reached its end
+    pub interrupted: bool,
+}
+
+#[derive(Default)]
+pub struct RailController {
+    rail: Option<(Handle<CameraRail>, CameraRail)>,
+    elapsed: f32,
+    next_keyframe: usize,
+    pending_events: Vec<String>,
+    finished: bool,
+}
+
+impl RailController {
+    fn apply_pose(&self, rig: &mut IsometricCamera) {
+        let Some((_, rail)) = &self.rail else {
+            return;
+        };
+        if let Some((pivot, angles)) = rail.sample(self.elapsed) {
+            rig.set_pose(pivot, angles.x, angles.y, angles.z);
+        }
+    }
+}
+
+impl CameraController for RailController {
+    fn enter(&mut self, rig: &mut IsometricCamera) {
+        self.apply_pose(rig);
+    }
+
+    fn update(&mut self, rig: &mut IsometricCamera, delta: f32) {
+        let Some((_, rail)) = &self.rail else {
+            return;
+        };
+        self.elapsed += delta;
+        while let Some(keyframe) = rail.keyframes.get(self.next_keyframe) {
+            if keyframe.time > self.elapsed {
+                break;
+            }
+            if let Some(event) = &keyframe.event {
+                self.pending_events.push(event.clone());
+            }
+            self.next_keyframe += 1;
+        }
+        self.finished = self.elapsed >= rail.duration();
+        self.apply_pose(rig);
+    }
+}
+
+pub(super) fn register_mode(mut camera_manager: ResMut<CameraManager>) {
+    camera_manager.register_mode_with_controller(
+        RAIL,
+        IsometricCamera::default(),
+        RailController::default(),
+    );
+}
+
+/**
+ * Starts queued rails once their asset has loaded, pushing the rail mode
+ * so that the previous mode is blended back to afterwards.
+ * Rails that failed to load are dropped from the queue
+ */
+pub(super) fn start_rails(
+    mut ev_play: EventReader<PlayCameraRail>,
+    mut queued: Local<Vec<Handle<CameraRail>>>,
+    rails: Res<Assets<CameraRail>>,
+    asset_server: Res<AssetServer>,
+    mut camera_manager: ResMut<CameraManager>,
+    mut ev_finished: EventWriter<CameraRailFinished>,
+) {
+    queued.extend(ev_play.read().map(|e| e.0.clone()));
+    queued.retain(|handle| {
+        let failed = matches!(asset_server.load_state(handle), LoadState::Failed(_));
+        if failed {
+            warn!("Camera rail failed to load: {:?}", handle.path());
+        }
+        !failed
+    });
+    let Some(index) = queued.iter().position(|h| rails.contains(h)) else {
+        return;
+    };
+    let handle = queued.remove(index);
+    let mut rail = rails.get(&handle).unwrap().clone();
+    rail.unwrap_yaws();
+
+    let Some(controller) = camera_manager.controller_mut::<RailController>(RAIL) else {
+        return;
+    };
+    if let Some((previous, _)) = controller.rail.take() {
+        ev_finished.send(CameraRailFinished {
+            rail: previous,
+            interrupted: !controller.finished,
+        });
+    }
+    *controller = RailController {
+        rail: Some((handle, rail)),
+        ..default()
+    };
+    if camera_manager.get_mode() != RAIL {
+        camera_manager.push_mode(RAIL);
+    }
+}
+
+pub(super) fn finish_rails(
+    mut camera_manager: ResMut<CameraManager>,
+    mut ev_keyframe: EventWriter<CameraRailKeyframe>,
+    mut ev_finished: EventWriter<CameraRailFinished>,
+) {
+    let Some(controller) = camera_manager.controller_mut::<RailController>(RAIL) else {
+        return;
+    };
+    for event in controller.pending_events.drain(..) {
+        ev_keyframe.send(CameraRailKeyframe(event));
+    }
+    if !controller.finished {
+        return;
+    }
+
+    let handle = controller.rail.take().map(|(h, _)| h);
+    controller.finished = false;
+    if let Some(rail) = handle {
+        ev_finished.send(CameraRailFinished {
+            rail,
+            interrupted: false,
+        });
+    }
+    if camera_manager.get_mode() == RAIL {
+        camera_manager.pop_mode();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(time: f32, x: f32, yaw: f32) -> RailKeyframe {
+        RailKeyframe {
+            time,
+            pivot: Vec3::new(x, 0., 0.),
+            yaw,
+            pitch: -30.,
+            arm_length: 10.,
+            easing: Easing::Linear,
+            event: None,
+        }
+    }
+
+    fn rail(keyframes: Vec<RailKeyframe>) -> CameraRail {
+        CameraRail { keyframes }
+    }
+
+    #[test]
+    fn sampling_passes_through_the_keyframes() {
+        let rail = rail(vec![
+            key(0., 0., 0.),
+            key(1., 10., 20.),
+            key(2., 20., 40.),
+            key(4., 30., 60.),
+        ]);
+        for key in &rail.keyframes {
+            let (pivot, angles) = rail.sample(key.time).unwrap();
+            assert!(pivot.distance(key.pivot) < 1e-4);
+            assert!(angles.distance(Vec3::new(key.yaw, key.pitch, key.arm_length)) < 1e-4);
+        }
+    }
+
+    #[test]
+    fn sampling_evenly_spaced_keyframes_is_linear() {
+        let rail = rail(vec![
+            key(0., 0., 0.),
+            key(1., 10., 10.),
+            key(2., 20., 20.),
+            key(3., 30., 30.),
+        ]);
+        let (pivot, angles) = rail.sample(1.5).unwrap();
+        assert!((pivot.x - 15.).abs() < 1e-4);
+        assert!((angles.x - 15.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn sampling_outside_the_rail_holds_the_ends() {
+        let long = rail(vec![key(0., 0., 0.), key(1., 10., 0.), key(2., 20., 0.)]);
+        assert!(long.sample(-1.).unwrap().0.distance(Vec3::ZERO) < 1e-4);
+        assert!(long.sample(5.).unwrap().0.distance(Vec3::new(20., 0., 0.)) < 1e-4);
+
+        let single = rail(vec![key(0., 3., 0.)]);
+        assert!((single.sample(1.).unwrap().0.x - 3.).abs() < 1e-4);
+        assert!(rail(Vec::new()).sample(0.).is_none());
+    }
+
+    #[test]
+    fn yaws_unwrap_to_the_shortest_turn() {
+        let mut rail = rail(vec![
+            key(0., 0., 350.),
+            key(1., 0., 10.),
+            key(2., 0., 300.),
+            key(3., 0., -170.),
+        ]);
+        rail.unwrap_yaws();
+        let yaws: Vec<f32> = rail.keyframes.iter().map(|k| k.yaw).collect();
+        assert_eq!(yaws, vec![350., 370., 300., 190.]);
+    }
+
+    #[test]
+    fn controller_reports_keyframe_events_and_finishes() {
+        let mut rail = rail(vec![key(0., 0., 0.), key(1., 10., 0.), key(2., 20., 0.)]);
+        rail.keyframes[1].event = Some("reveal".to_string());
+        let mut controller = RailController {
+            rail: Some((Handle::default(), rail)),
+            ..default()
+        };
+        let mut rig = IsometricCamera::default();
+
+        controller.update(&mut rig, 0.5);
+        assert!(controller.pending_events.is_empty());
+        controller.update(&mut rig, 0.5);
+        assert_eq!(controller.pending_events, vec!["reveal".to_string()]);
+        assert!(!controller.finished);
+        controller.update(&mut rig, 1.);
+        assert!(controller.finished);
+        assert!(rig.pivot.distance(Vec3::new(20., 0., 0.)) < 1e-4);
+    }
+}
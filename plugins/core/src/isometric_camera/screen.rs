@@ -0,0 +1,159 @@
+use bevy::{prelude::*, utils::HashMap, window::PrimaryWindow};
+
+use super::{viewports::CameraRole, viewports::ManagedCamera, CameraManager};
+
+/**
+ * Matrices of a rendered camera from the end of last frame, so conversions
+ * don't need access to the camera entity
+ */
+#[derive(Debug, Clone, Copy)]
+pub(super) struct CachedView {
+    world_from_clip: Mat4,
+    clip_from_world: Mat4,
+    // logical pixels
+    viewport: Rect,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct ScreenState {
+    views: HashMap<CameraRole, CachedView>,
+    cursor: Option<Vec2>,
+}
+
+impl CachedView {
+    fn screen_to_ray(&self, screen: Vec2) -> Option<Ray3d> {
+        let size = self.viewport.size();
+        if size.x <= 0. || size.y <= 0. {
+            return None;
+        }
+        let uv = (screen - self.viewport.min) / size;
+        let ndc = Vec2::new(uv.x * 2. - 1., 1. - uv.y * 2.);
+
+        // reverse z, the near plane is at 1 and far approaches 0
+        let near = self.world_from_clip.project_point3(ndc.extend(1.));
+        let far = self
+            .world_from_clip
+            .project_point3(ndc.extend(f32::EPSILON));
+        let direction = Dir3::new(far - near).ok()?;
+        Some(Ray3d {
+            origin: near,
+            direction,
+        })
+    }
+
+    fn world_to_screen(&self, world: Vec3) -> Option<Vec2> {
+        let clip = self.clip_from_world * world.extend(1.);
+        if clip.w <= 0. {
+            return None;
+        }
+        let ndc = clip.truncate() / clip.w;
+        let uv = Vec2::new(ndc.x + 1., 1. - ndc.y) * 0.5;
+        Some(self.viewport.min + uv * self.viewport.size())
+    }
+}
+
+impl CameraManager {
+    /**
+     * Ray through a position in logical window pixels, from the main camera
+     */
+    pub fn screen_to_ray(&self, screen: Vec2) -> Option<Ray3d> {
+        self.screen_to_ray_for(CameraRole::Player(0), screen)
+    }
+
+    pub fn screen_to_ray_for(&self, role: CameraRole, screen: Vec2) -> Option<Ray3d> {
+        self.screen.views.get(&role)?.screen_to_ray(screen)
+    }
+
+    /**
+     * Ray through the cursor, None while the cursor is outside the window
+     */
+    pub fn cursor_ray(&self) -> Option<Ray3d> {
+        self.screen_to_ray(self.screen.cursor?)
+    }
+
+    pub fn cursor_position(&self) -> Option<Vec2> {
+        self.screen.cursor
+    }
+
+    pub fn screen_to_plane(&self, screen: Vec2, origin: Vec3, normal: Dir3) -> Option<Vec3> {
+        let ray = self.screen_to_ray(screen)?;
+        let distance = ray.intersect_plane(origin, InfinitePlane3d { normal })?;
+        Some(ray.get_point(distance))
+    }
+
+    /**
+     * Intersects the screen ray with a heightfield, given as the terrain
+     * height at a world xz position. Marches the ray in `step` increments
+     * up to `max_distance`, then refines the hit by bisection
+     */
+    pub fn screen_to_terrain(
+        &self,
+        screen: Vec2,
+        height_at: impl Fn(Vec2) -> f32,
+        step: f32,
+        max_distance: f32,
+    ) -> Option<Vec3> {
+        let ray = self.screen_to_ray(screen)?;
+        let above = |distance: f32| {
+            let point = ray.get_point(distance);
+            point.y - height_at(Vec2::new(point.x, point.z))
+        };
+
+        let step = step.max(0.01);
+        let mut previous = 0.;
+        let mut distance = step;
+        while distance <= max_distance {
+            if above(distance) <= 0. {
+                let (mut low, mut high) = (previous, distance);
+                for _ in 0..16 {
+                    let mid = (low + high) * 0.5;
+                    if above(mid) > 0. {
+                        low = mid;
+                    } else {
+                        high = mid;
+                    }
+                }
+                return Some(ray.get_point(high));
+            }
+            previous = distance;
+            distance += step;
+        }
+        None
+    }
+
+    /**
+     * Logical window pixels of a world position as seen by the main camera,
+     * None if it is behind the camera
+     */
+    pub fn world_to_screen(&self, world: Vec3) -> Option<Vec2> {
+        self.world_to_screen_for(CameraRole::Player(0), world)
+    }
+
+    pub fn world_to_screen_for(&self, role: CameraRole, world: Vec3) -> Option<Vec2> {
+        self.screen.views.get(&role)?.world_to_screen(world)
+    }
+}
+
+pub(super) fn cache_views(
+    mut camera_manager: ResMut<CameraManager>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&ManagedCamera, &Camera, &GlobalTransform)>,
+) {
+    let state = &mut camera_manager.screen;
+    state.cursor = window.get_single().ok().and_then(|w| w.cursor_position());
+    state.views.clear();
+    for (managed, camera, transform) in &cameras {
+        let Some(viewport) = camera.logical_viewport_rect() else {
+            continue;
+        };
+        let clip_from_world = camera.clip_from_view() * transform.compute_matrix().inverse();
+        state.views.insert(
+            managed.role,
+            CachedView {
+                world_from_clip: clip_from_world.inverse(),
+                clip_from_world,
+                viewport,
+            },
+        );
+    }
+}
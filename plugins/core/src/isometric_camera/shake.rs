@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+
+use super::CameraManager;
+
+#[derive(Debug, Clone)]
+pub struct ShakeSettings {
+    // translation at full trauma, world units
+    pub max_offset: f32,
+    // yaw, pitch and roll at full trauma, degrees
+    pub max_angle: f32,
+    // noise samples per second, higher is more jittery
+    pub frequency: f32,
+    // oscillations per second of a directional impulse
+    pub impulse_frequency: f32,
+    // how quickly an impulse settles, per second
+    pub impulse_damping: f32,
+}
+
+impl Default for ShakeSettings {
+    fn default() -> Self {
+        Self {
+            max_offset: 0.5,
+            max_angle: 3.,
+            frequency: 15.,
+            impulse_frequency: 6.,
+            impulse_damping: 5.,
+        }
+    }
+}
+
+/**
+ * Trauma from a single source, decaying linearly per second.
+ * Adding trauma for a named source again tops it up instead of stacking
+ */
+#[derive(Debug, Clone)]
+pub struct TraumaSource {
+    pub name: &'static str,
+    pub trauma: f32,
+    pub decay: f32,
+}
+
+#[derive(Debug, Clone)]
+struct Impulse {
+    offset: Vec3,
+    elapsed: f32,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct ShakeState {
+    pub settings: ShakeSettings,
+    sources: Vec<TraumaSource>,
+    impulses: Vec<Impulse>,
+    time: f32,
+}
+
+impl ShakeState {
+    fn trauma(&self) -> f32 {
+        self.sources.iter().map(|s| s.trauma).sum::<f32>().min(1.)
+    }
+
+    pub fn advance(&mut self, delta: f32) {
+        self.time += delta;
+        for source in self.sources.iter_mut() {
+            source.trauma -= source.decay * delta;
+        }
+        self.sources.retain(|s| s.trauma > 0.);
+
+        let damping = self.settings.impulse_damping;
+        for impulse in self.impulses.iter_mut() {
+            impulse.elapsed += delta;
+        }
+        // an impulse is negligible after it has decayed below 1%
+        self.impulses
+            .retain(|i| (-damping * i.elapsed).exp() > 0.01);
+    }
+
+    /**
+     * Additive offset on top of the rig transform, the rig itself is never touched
+     */
+    pub fn apply(&self, transform: Transform) -> Transform {
+        let shake = self.trauma().powi(2);
+        if shake <= 0. && self.impulses.is_empty() {
+            return transform;
+        }
+
+        let t = self.time * self.settings.frequency;
+        let offset =
+            Vec3::new(noise(t, 0.), noise(t, 1.), noise(t, 2.)) * self.settings.max_offset * shake;
+        let (yaw, pitch, roll) = (
+            (noise(t, 3.) * self.settings.max_angle * shake).to_radians(),
+            (noise(t, 4.) * self.settings.max_angle * shake).to_radians(),
+            (noise(t, 5.) * self.settings.max_angle * shake).to_radians(),
+        );
+
+        let impulse = self
+            .impulses
+            .iter()
+            .map(|i| {
+                let envelope = (-self.settings.impulse_damping * i.elapsed).exp();
+                let wave =
+                    (std::f32::consts::TAU * self.settings.impulse_frequency * i.elapsed).cos();
+                i.offset * envelope * wave
+            })
+            .sum::<Vec3>();
+
+        let mut transform = transform;
+        transform.translation += transform.rotation * offset + impulse;
+        transform.rotation *= Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll);
+        transform
+    }
+}
+
+// cheap smooth noise in [-1, 1] from a few incommensurate sines
+fn noise(t: f32, seed: f32) -> f32 {
+    let s = seed * 17.13;
+    (t + s).sin() * 0.5 + (t * 2.31 + s * 1.7).sin() * 0.3 + (t * 4.17 + s * 2.9).sin() * 0.2
+}
+
+impl CameraManager {
+    pub fn add_trauma(&mut self, source: TraumaSource) {
+        let sources = &mut self.shake.sources;
+        match sources.iter_mut().find(|s| s.name == source.name) {
+            Some(existing) => {
+                existing.trauma = (existing.trauma.max(source.trauma)).min(1.);
+                existing.decay = source.decay;
+            }
+            None => sources.push(TraumaSource {
+                trauma: source.trauma.min(1.),
+                ..source
+            }),
+        }
+    }
+
+    /**
+     * Kicks the camera along `direction`, e.g. away from an explosion
+     */
+    pub fn add_impulse(&mut self, direction: Vec3, strength: f32) {
+        self.shake.impulses.push(Impulse {
+            offset: direction.normalize_or_zero() * strength,
+            elapsed: 0.,
+        });
+    }
+
+    /**
+     * Trauma and an impulse away from `origin`, both falling off with distance
+     */
+    pub fn add_explosion(&mut self, origin: Vec3, strength: f32, radius: f32) {
+        let pivot = self.current_pose().pivot;
+        let falloff = 1. - (pivot.distance(origin) / radius.max(f32::EPSILON)).min(1.);
+        if falloff <= 0. {
+            return;
+        }
+        self.add_trauma(TraumaSource {
+            name: "explosion",
+            trauma: strength * falloff,
+            decay: 1.,
+        });
+        self.add_impulse(pivot - origin, strength * falloff * 0.5);
+    }
+
+    pub fn shake_settings_mut(&mut self) -> &mut ShakeSettings {
+        &mut self.shake.settings
+    }
+}
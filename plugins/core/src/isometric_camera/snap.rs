@@ -0,0 +1,196 @@
+use crate::easing::Easing;
+
+use super::{CameraManager, CameraMode, IsometricCamera};
+
+/**
+ * Diorama style yaw, rotating in fixed steps between preset angles
+ */
+#[derive(Debug, Clone)]
+pub struct YawSnap {
+    // presets per full turn, 8 gives 45 degree steps and 4 gives 90
+    pub steps: u32,
+    // angle of the first preset in degrees
+    pub offset: f32,
+    // seconds per step
+    pub duration: f32,
+    pub easing: Easing,
+    // accumulated yaw input needed before a step is queued
+    pub input_threshold: f32,
+    pub max_queued_steps: u32,
+}
+
+impl Default for YawSnap {
+    fn default() -> Self {
+        Self {
+            steps: 8,
+            offset: 45.,
+            duration: 0.35,
+            easing: Easing::CubicInOut,
+            input_threshold: 1.,
+            max_queued_steps: 2,
+        }
+    }
+}
+
+impl YawSnap {
+    fn step_angle(&self) -> f32 {
+        360. / self.steps.max(1) as f32
+    }
+
+    fn nearest_preset(&self, yaw: f32) -> f32 {
+        let step = self.step_angle();
+        self.offset + ((yaw - self.offset) / step).round() * step
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct SnapState {
+    accumulated_input: f32,
+    from: f32,
+    target: Option<f32>,
+    elapsed: f32,
+    // seconds from `from` to `target`, longer than one step when steps are queued
+    duration: f32,
+}
+
+impl IsometricCamera {
+    pub(super) fn queue_yaw_step(&mut self, rotation: f32) {
+        let Some(snap) = &self.yaw_snap else {
+            return;
+        };
+
+        let state = &mut self.snap_state;
+        state.accumulated_input += rotation;
+        if state.accumulated_input.abs() < snap.input_threshold {
+            return;
+        }
+
+        let step = snap.step_angle() * state.accumulated_input.signum();
+        state.accumulated_input = 0.;
+        let base = state
+            .target
+            .unwrap_or_else(|| snap.nearest_preset(self.angle_yaw));
+        let target = base + step;
+        if (target - self.angle_yaw).abs() > snap.step_angle() * snap.max_queued_steps as f32 {
+            return;
+        }
+        if self.limits.yaw.is_some_and(|limit| !limit.contains(target)) {
+            return;
+        }
+
+        // a step queued mid-rotation carries on from the current yaw,
+        // keeping the time left of the running step
+        let remaining = match state.target {
+            Some(_) => (state.duration - state.elapsed).max(0.),
+            None => 0.,
+        };
+        state.from = self.angle_yaw;
+        state.target = Some(target);
+        state.elapsed = 0.;
+        state.duration = remaining + snap.duration;
+    }
+
+    fn snap_to_nearest_preset(&mut self) {
+        let Some(snap) = &self.yaw_snap else {
+            return;
+        };
+        self.snap_state = SnapState {
+            from: self.angle_yaw,
+            target: Some(snap.nearest_preset(self.angle_yaw)),
+            duration: snap.duration,
+            ..Default::default()
+        };
+    }
+
+    pub(super) fn update_yaw_snap(&mut self, delta: f32) {
+        let (Some(snap), Some(target)) = (&self.yaw_snap, self.snap_state.target) else {
+            return;
+        };
+
+        let state = &mut self.snap_state;
+        state.elapsed += delta;
+        if state.duration <= 0. || state.elapsed >= state.duration {
+            self.angle_yaw = target;
+            state.target = None;
+            return;
+        }
+        let t = snap.easing.sample(state.elapsed / state.duration);
+        self.angle_yaw = state.from + (target - state.from) * t;
+    }
+}
+
+impl CameraManager {
+    /**
+     * None gives free yaw, which is what the editor camera uses. The game
+     * camera snaps with the default YawSnap until changed.
+     * Returns false if `mode` isn't registered
+     */
+    pub fn set_yaw_snap(&mut self, mode: CameraMode, snap: Option<YawSnap>) -> bool {
+        let Some(camera) = self.rig_mut(mode) else {
+            return false;
+        };
+        camera.yaw_snap = snap;
+        camera.snap_state = SnapState::default();
+        camera.snap_to_nearest_preset();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::isometric_camera::limits::Limit;
+
+    fn snapping_rig() -> IsometricCamera {
+        IsometricCamera {
+            yaw_snap: Some(YawSnap::default()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn small_input_accumulates_before_a_step() {
+        let mut rig = snapping_rig();
+        rig.queue_yaw_step(0.6);
+        assert_eq!(rig.snap_state.target, None);
+        rig.queue_yaw_step(0.6);
+        assert_eq!(rig.snap_state.target, Some(90.));
+    }
+
+    #[test]
+    fn steps_queue_up_to_the_maximum() {
+        let mut rig = snapping_rig();
+        rig.queue_yaw_step(1.);
+        rig.queue_yaw_step(1.);
+        assert_eq!(rig.snap_state.target, Some(135.));
+        rig.queue_yaw_step(1.);
+        assert_eq!(rig.snap_state.target, Some(135.));
+
+        rig.update_yaw_snap(1.);
+        assert_eq!(rig.angle_yaw, 135.);
+        assert_eq!(rig.snap_state.target, None);
+    }
+
+    #[test]
+    fn queued_step_continues_from_the_current_yaw() {
+        let mut rig = snapping_rig();
+        rig.queue_yaw_step(-1.);
+        rig.update_yaw_snap(0.2);
+        let yaw = rig.angle_yaw;
+        assert!(yaw < 45. && yaw > 0.);
+
+        rig.queue_yaw_step(-1.);
+        assert_eq!(rig.snap_state.from, yaw);
+        assert_eq!(rig.snap_state.target, Some(-45.));
+        assert!((rig.snap_state.duration - (0.15 + 0.35)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn steps_past_a_yaw_limit_are_dropped() {
+        let mut rig = snapping_rig();
+        rig.limits.yaw = Some(Limit::new(0., 90.));
+        rig.queue_yaw_step(1.);
+        rig.queue_yaw_step(1.);
+        assert_eq!(rig.snap_state.target, Some(90.));
+    }
+}
@@ -0,0 +1,244 @@
+use bevy::{
+    prelude::*,
+    render::camera::{ScalingMode, Viewport},
+    window::PrimaryWindow,
+};
+
+use super::{CameraManager, IsometricCamera};
+
+/**
+ * Which view a camera entity renders. Player(0) is the main camera,
+ * driven by the active CameraMode, the other players each own a rig
+ */
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum CameraRole {
+    Player(usize),
+    Minimap,
+}
+
+/**
+ * Only cameras with this marker are driven by the CameraManager,
+ * so apps are free to spawn their own UI or debug cameras
+ */
+#[derive(Debug, Component)]
+pub struct ManagedCamera {
+    pub role: CameraRole,
+}
+
+#[derive(Debug, Clone)]
+pub struct MinimapSettings {
+    // side length of the square viewport as a fraction of the window height
+    pub size: f32,
+    // margin to the top right window corner in physical pixels
+    pub margin: u32,
+    // visible world-space height
+    pub view_height: f32,
+    pub altitude: f32,
+}
+
+impl Default for MinimapSettings {
+    fn default() -> Self {
+        Self {
+            size: 0.25,
+            margin: 16,
+            view_height: 40.,
+            altitude: 100.,
+        }
+    }
+}
+
+impl CameraManager {
+    /**
+     * Splits the window between `count` local players, at most four
+     */
+    pub fn set_local_players(&mut self, count: usize) {
+        let extra = count.clamp(1, 4) - 1;
+        while self.player_cameras.len() < extra {
+            let mut rig = self.get().clone();
+            rig.zoom_target = None;
+            self.player_cameras.push(rig);
+        }
+        self.player_cameras.truncate(extra);
+    }
+
+    pub fn local_players(&self) -> usize {
+        self.player_cameras.len() + 1
+    }
+
+    /**
+     * Rig of a split-screen player, player 0 is controlled through the
+     * CameraManager's own methods and the active CameraMode
+     */
+    pub fn player_camera_mut(&mut self, player: usize) -> Option<&mut IsometricCamera> {
+        match player {
+            0 => Some(self.get_mut()),
+            _ => self.player_cameras.get_mut(player - 1),
+        }
+    }
+
+    pub fn set_minimap(&mut self, minimap: Option<MinimapSettings>) {
+        self.minimap = minimap;
+    }
+
+    fn roles(&self) -> Vec<CameraRole> {
+        let mut roles = (0..self.local_players())
+            .map(CameraRole::Player)
+            .collect::<Vec<_>>();
+        if self.minimap.is_some() {
+            roles.push(CameraRole::Minimap);
+        }
+        roles
+    }
+
+    // player 0 uses the mode rigs, the others have their own
+    fn player_camera(&self, player: usize) -> Option<&IsometricCamera> {
+        self.player_cameras.get(player.wrapping_sub(1))
+    }
+
+    /**
+     * None for a player whose rig is gone, e.g. removed in the same frame
+     */
+    pub(super) fn role_transform(&self, role: CameraRole) -> Option<Transform> {
+        match role {
+            CameraRole::Player(0) => Some(self.get_camera_transform()),
+            CameraRole::Player(player) => self
+                .player_camera(player)
+                .map(IsometricCamera::get_camera_transform),
+            CameraRole::Minimap => {
+                let pivot = self.current_pose().pivot;
+                let altitude = self.minimap.as_ref().map_or(100., |m| m.altitude);
+                Some(
+                    Transform::from_translation(pivot + Vec3::Y * altitude)
+                        .looking_at(pivot, Vec3::NEG_Z),
+                )
+            }
+        }
+    }
+
+    pub(super) fn role_projection(&self, role: CameraRole) -> Option<Projection> {
+        match role {
+            CameraRole::Player(0) => Some(self.current_pose().projection()),
+            CameraRole::Player(player) => {
+                self.player_camera(player).map(IsometricCamera::projection)
+            }
+            CameraRole::Minimap => Some(Projection::Orthographic(OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical {
+                    viewport_height: self.minimap.as_ref().map_or(40., |m| m.view_height),
+                },
+                ..OrthographicProjection::default_3d()
+            })),
+        }
+    }
+
+    fn role_viewport(&self, role: CameraRole, window: UVec2) -> Option<Viewport> {
+        match role {
+            CameraRole::Player(player) => {
+                let players = self.local_players() as u32;
+                if players <= 1 {
+                    return None;
+                }
+                let (columns, rows) = if players == 2 { (2, 1) } else { (2, 2) };
+                let size = UVec2::new(window.x / columns, window.y / rows);
+                let cell = UVec2::new(player as u32 % columns, player as u32 / columns);
+                Some(Viewport {
+                    physical_position: cell * size,
+                    physical_size: size,
+                    ..default()
+                })
+            }
+            CameraRole::Minimap => {
+                let minimap = self.minimap.as_ref()?;
+                let side = ((window.y as f32 * minimap.size) as u32).max(1);
+                Some(Viewport {
+                    physical_position: UVec2::new(
+                        window.x.saturating_sub(side + minimap.margin),
+                        minimap.margin,
+                    ),
+                    physical_size: UVec2::splat(side),
+                    ..default()
+                })
+            }
+        }
+    }
+}
+
+fn camera_order(role: CameraRole) -> isize {
+    match role {
+        CameraRole::Player(player) => player as isize,
+        // drawn on top of every player view
+        CameraRole::Minimap => 10,
+    }
+}
+
+pub(super) fn spawn_managed_camera(
+    commands: &mut Commands,
+    camera_manager: &CameraManager,
+    role: CameraRole,
+) {
+    let (Some(projection), Some(transform)) = (
+        camera_manager.role_projection(role),
+        camera_manager.role_transform(role),
+    ) else {
+        return;
+    };
+    let mut camera = commands.spawn((
+        Camera3d::default(),
+        Camera {
+            order: camera_order(role),
+            ..default()
+        },
+        projection,
+        transform,
+        ManagedCamera { role },
+    ));
+    // untargeted UI like the HUD and the fade would otherwise go to the minimap, the highest order
+    if role == CameraRole::Player(0) {
+        camera.insert(IsDefaultUiCamera);
+    }
+}
+
+/**
+ * Spawns and despawns camera entities to match the player count and minimap
+ */
+pub(super) fn sync_cameras(
+    mut commands: Commands,
+    camera_manager: Res<CameraManager>,
+    cameras: Query<(Entity, &ManagedCamera)>,
+) {
+    let roles = camera_manager.roles();
+    for (entity, camera) in &cameras {
+        if !roles.contains(&camera.role) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    for role in roles {
+        if !cameras.iter().any(|(_, c)| c.role == role) {
+            spawn_managed_camera(&mut commands, &camera_manager, role);
+        }
+    }
+}
+
+pub(super) fn update_viewports(
+    camera_manager: Res<CameraManager>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<(&ManagedCamera, &mut Camera)>,
+) {
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    let size = window.physical_size();
+    for (managed, mut camera) in &mut cameras {
+        let viewport = camera_manager.role_viewport(managed.role, size);
+        let unchanged = match (&camera.viewport, &viewport) {
+            (None, None) => true,
+            (Some(current), Some(next)) => {
+                current.physical_position == next.physical_position
+                    && current.physical_size == next.physical_size
+            }
+            _ => false,
+        };
+        if !unchanged {
+            camera.viewport = viewport;
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use bevy::{prelude::*, render::camera::ScalingMode};
+
+use super::{CameraManager, CameraMode, IsometricCamera};
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum ProjectionKind {
+    #[default]
+    Perspective,
+    Orthographic,
+}
+
+/**
+ * Zoom limits, in spring arm length for perspective
+ * and in visible world-space height for orthographic
+ */
+#[derive(Debug, Clone)]
+pub struct ZoomSettings {
+    pub min_arm_length: f32,
+    pub max_arm_length: f32,
+    pub min_ortho_height: f32,
+    pub max_ortho_height: f32,
+    // exponential smoothing rate per second
+    pub smoothing: f32,
+}
+
+impl Default for ZoomSettings {
+    fn default() -> Self {
+        Self {
+            min_arm_length: 4.,
+            max_arm_length: 60.,
+            min_ortho_height: 4.,
+            max_ortho_height: 60.,
+            smoothing: 10.,
+        }
+    }
+}
+
+impl IsometricCamera {
+    pub(super) fn zoom_value(&self) -> f32 {
+        match self.projection {
+            ProjectionKind::Perspective => self.spring_arm_length,
+            ProjectionKind::Orthographic => self.ortho_height,
+        }
+    }
+
+    fn zoom_limits(&self) -> (f32, f32) {
+        match self.projection {
+            ProjectionKind::Perspective => (
+                self.limits.clamp_arm_length(self.zoom.min_arm_length),
+                self.limits.clamp_arm_length(self.zoom.max_arm_length),
+            ),
+            ProjectionKind::Orthographic => {
+                (self.zoom.min_ortho_height, self.zoom.max_ortho_height)
+            }
+        }
+    }
+
+    pub(super) fn set_zoom_target(&mut self, value: f32) {
+        let (min, max) = self.zoom_limits();
+        // not clamp(), which panics if settings changed by hand left min above max
+        self.zoom_target = Some(value.max(min).min(max));
+    }
+
+    pub(super) fn update_zoom(&mut self, delta: f32) {
+        let Some(target) = self.zoom_target else {
+            return;
+        };
+
+        let current = self.zoom_value();
+        let mut next = current + (target - current) * (1. - (-self.zoom.smoothing * delta).exp());
+        if (target - next).abs() < 0.001 {
+            next = target;
+            self.zoom_target = None;
+        }
+        match self.projection {
+            ProjectionKind::Perspective => self.spring_arm_length = next,
+            ProjectionKind::Orthographic => self.ortho_height = next,
+        }
+    }
+
+    pub(super) fn projection(&self) -> Projection {
+        match self.projection {
+            ProjectionKind::Perspective => {
+                Projection::Perspective(PerspectiveProjection::default())
+            }
+            ProjectionKind::Orthographic => Projection::Orthographic(OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical {
+                    viewport_height: self.ortho_height,
+                },
+                ..OrthographicProjection::default_3d()
+            }),
+        }
+    }
+}
+
+impl CameraManager {
+    /**
+     * Positive amounts zoom in, in units of the active projection
+     */
+    pub fn zoom(&mut self, amount: f32) {
+        let camera = self.get_mut();
+        let from = camera.zoom_target.unwrap_or(camera.zoom_value());
+        camera.set_zoom_target(from - amount);
+    }
+
+    pub fn set_zoom(&mut self, value: f32) {
+        self.get_mut().set_zoom_target(value)
+    }
+
+    /**
+     * Returns false if `mode` isn't registered
+     */
+    pub fn set_projection(&mut self, mode: CameraMode, projection: ProjectionKind) -> bool {
+        let Some(camera) = self.rig_mut(mode) else {
+            return false;
+        };
+        camera.projection = projection;
+        camera.zoom_target = None;
+        true
+    }
+
+    pub fn get_projection(&self, mode: CameraMode) -> Option<ProjectionKind> {
+        self.cameras.get(&mode).map(|camera| camera.projection)
+    }
+
+    /**
+     * Swapped min and max values are put back in order.
+     * Returns false if `mode` isn't registered
+     */
+    pub fn set_zoom_settings(&mut self, mode: CameraMode, settings: ZoomSettings) -> bool {
+        let Some(camera) = self.rig_mut(mode) else {
+            return false;
+        };
+        camera.zoom = ZoomSettings {
+            min_arm_length: settings.min_arm_length.min(settings.max_arm_length),
+            max_arm_length: settings.min_arm_length.max(settings.max_arm_length),
+            min_ortho_height: settings.min_ortho_height.min(settings.max_ortho_height),
+            max_ortho_height: settings.min_ortho_height.max(settings.max_ortho_height),
+            ..settings
+        };
+        true
+    }
+}
+
+pub(super) fn apply_projection(current: &mut Projection, desired: Projection) {
+    match (current, desired) {
+        (Projection::Orthographic(current), Projection::Orthographic(desired)) => {
+            current.scaling_mode = desired.scaling_mode;
+        }
+        (Projection::Perspective(_), Projection::Perspective(_)) => (),
+        (current, desired) => *current = desired,
+    }
+}
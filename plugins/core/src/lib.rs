@@ -1,6 +1,17 @@
+pub mod character_animation;
+pub mod collectibles;
+pub mod easing;
 pub mod exit_game;
+pub mod health;
 pub mod input_manager;
 pub mod isometric_camera;
+pub mod player_controller;
+pub mod respawn;
+pub mod ron_asset;
+pub mod skills;
+pub mod temporary_skills;
+pub mod tollgates;
+pub mod volume;
 
 pub struct CorePlugin;
 impl bevy::prelude::Plugin for CorePlugin {
@@ -8,7 +19,15 @@ impl bevy::prelude::Plugin for CorePlugin {
         app.add_plugins((
             exit_game::ExitGamePlugin,
             input_manager::InputManagerPlugin,
-            isometric_camera::IsometricCameraPlugin,
+            isometric_camera::IsometricCameraPlugin::default(),
+            player_controller::PlayerControllerPlugin,
+            character_animation::CharacterAnimationPlugin,
+            skills::SkillsPlugin,
+            temporary_skills::TemporarySkillsPlugin::default(),
+            collectibles::CollectiblesPlugin,
+            tollgates::TollgatesPlugin,
+            respawn::RespawnPlugin,
+            health::HealthPlugin,
         ));
     }
 }
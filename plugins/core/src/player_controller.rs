@@ -0,0 +1,429 @@
+use bevy::{
+    picking::mesh_picking::ray_cast::{MeshRayCast, RayCastSettings},
+    prelude::*,
+    utils::HashSet,
+};
+use serde::Deserialize;
+
+use crate::{input_manager as input, isometric_camera::CameraManager, ron_asset::RonAssetPlugin};
+
+pub mod glide;
+pub mod jump;
+
+const UP: Dir3 = Dir3::Y;
+// gap kept between the character and walls it slides along
+const SKIN: f32 = 0.02;
+
+pub struct PlayerControllerPlugin;
+
+impl Plugin for PlayerControllerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<PlayerTuning>::new(&["controller.ron"]))
+            .add_event::<jump::PlayerJumped>()
+            .add_event::<jump::PlayerLanded>()
+            .add_event::<glide::GlideStarted>()
+            .add_event::<glide::GlideEnded>()
+            .add_systems(
+                Update,
+                (
+                    apply_tuning,
+                    jump::jump_players,
+                    glide::glide_players,
+                    move_players,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/**
+ * Tuning for grounded movement, distances in world units and angles in degrees
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MovementSettings {
+    // units per second at full input
+    pub max_speed: f32,
+    // units per second squared
+    pub acceleration: f32,
+    pub deceleration: f32,
+    // degrees per second the character turns towards its movement
+    pub turn_speed: f32,
+    // yaw added to the facing, for models not facing +Z
+    pub model_yaw_offset: f32,
+    // steepest walkable ground
+    pub max_slope: f32,
+    // tallest ledge walked onto without jumping
+    pub step_height: f32,
+    // how far down the character sticks to the ground when walking off slopes and steps
+    pub snap_distance: f32,
+    // distance kept to walls
+    pub radius: f32,
+    pub gravity: f32,
+    pub max_fall_speed: f32,
+}
+
+impl Default for MovementSettings {
+    fn default() -> Self {
+        Self {
+            max_speed: 5.,
+            acceleration: 30.,
+            deceleration: 40.,
+            turn_speed: 720.,
+            model_yaw_offset: 0.,
+            max_slope: 45.,
+            step_height: 0.3,
+            snap_distance: 0.3,
+            radius: 0.4,
+            gravity: 20.,
+            max_fall_speed: 30.,
+        }
+    }
+}
+
+/**
+ * Controller tuning as a data asset, loaded from `*.controller.ron` files
+ */
+#[derive(Asset, TypePath, Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PlayerTuning {
+    pub movement: MovementSettings,
+    pub jump: jump::JumpSettings,
+    pub glide: glide::GlideSettings,
+}
+
+/**
+ * Kinematic character moved by a motion action, relative to the yaw of the
+ * active camera. The transform's translation is the character's feet
+ */
+#[derive(Component, Debug, Clone)]
+pub struct PlayerController {
+    pub move_action: input::Action,
+    pub settings: MovementSettings,
+    pub jump_action: Option<input::Action>,
+    pub jump: jump::JumpSettings,
+    pub glide_action: Option<input::Action>,
+    pub glide: glide::GlideSettings,
+    // overrides the settings above once loaded, and again on every hot reload
+    tuning: Option<(Handle<PlayerTuning>, bool)>,
+    velocity: Vec3,
+    grounded: bool,
+    ground_normal: Vec3,
+    jump_state: jump::JumpState,
+    glide_state: glide::GlideState,
+}
+
+impl PlayerController {
+    pub fn new(move_action: input::Action) -> Self {
+        Self {
+            move_action,
+            settings: MovementSettings::default(),
+            jump_action: None,
+            jump: jump::JumpSettings::default(),
+            glide_action: None,
+            glide: glide::GlideSettings::default(),
+            tuning: None,
+            velocity: Vec3::ZERO,
+            grounded: false,
+            ground_normal: Vec3::Y,
+            jump_state: jump::JumpState::default(),
+            glide_state: glide::GlideState::default(),
+        }
+    }
+
+    pub fn with_settings(mut self, settings: MovementSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn with_tuning(mut self, tuning: Handle<PlayerTuning>) -> Self {
+        self.tuning = Some((tuning, false));
+        self
+    }
+
+    pub fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    pub fn horizontal_speed(&self) -> f32 {
+        Vec3::new(self.velocity.x, 0., self.velocity.z).length()
+    }
+
+    /**
+     * Horizontal direction the character faces, undoing the model yaw offset
+     */
+    pub fn facing(&self, transform: &Transform) -> Vec3 {
+        let offset = Quat::from_rotation_y(-self.settings.model_yaw_offset.to_radians());
+        let forward = (transform.rotation * offset).mul_vec3(Vec3::Z);
+        Vec3::new(forward.x, 0., forward.z).normalize_or(Vec3::Z)
+    }
+
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    pub fn ground_normal(&self) -> Vec3 {
+        self.ground_normal
+    }
+
+    /**
+     * Drops all momentum and jump/glide state, e.g. after teleporting the character
+     */
+    pub fn reset_motion(&mut self) {
+        self.velocity = Vec3::ZERO;
+        self.grounded = false;
+        self.ground_normal = Vec3::Y;
+        self.jump_state = jump::JumpState::default();
+        self.glide_state = glide::GlideState::default();
+    }
+
+    /**
+     * Adds to the velocity, e.g. knockback. Upward impulses lift the character off the ground
+     */
+    pub fn add_impulse(&mut self, impulse: Vec3) {
+        self.velocity += impulse;
+        if impulse.y > 0. {
+            self.grounded = false;
+        }
+    }
+
+    fn is_walkable(&self, normal: Vec3) -> bool {
+        normal.angle_between(Vec3::Y) <= self.settings.max_slope.to_radians()
+    }
+}
+
+/**
+ * Entities (and their children) player controllers walk through, like
+ * pickups and trigger volumes with a visible mesh
+ */
+#[derive(Component)]
+pub struct ControllerCollisionIgnore;
+
+fn move_towards(current: Vec3, target: Vec3, max_delta: f32) -> Vec3 {
+    let difference = target - current;
+    if difference.length() <= max_delta {
+        return target;
+    }
+    current + difference.normalize() * max_delta
+}
+
+fn apply_tuning(
+    mut ev_asset: EventReader<AssetEvent<PlayerTuning>>,
+    tunings: Res<Assets<PlayerTuning>>,
+    mut players: Query<&mut PlayerController>,
+) {
+    let modified: HashSet<AssetId<PlayerTuning>> = ev_asset
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } | AssetEvent::LoadedWithDependencies { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for mut controller in &mut players {
+        let Some((handle, applied)) = &controller.tuning else {
+            continue;
+        };
+        if *applied && !modified.contains(&handle.id()) {
+            continue;
+        }
+        let Some(tuning) = tunings.get(handle) else {
+            continue;
+        };
+        controller.settings = tuning.movement.clone();
+        controller.jump = tuning.jump.clone();
+        controller.glide = tuning.glide.clone();
+        if let Some((_, applied)) = &mut controller.tuning {
+            *applied = true;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn move_players(
+    time: Res<Time>,
+    im: Res<input::InputManager>,
+    camera_manager: Res<CameraManager>,
+    mut ray_cast: MeshRayCast,
+    mut players: Query<(Entity, &mut PlayerController, &mut Transform)>,
+    ignored: Query<(), With<ControllerCollisionIgnore>>,
+    parents: Query<&Parent>,
+    mut ev_landed: EventWriter<jump::PlayerLanded>,
+) {
+    let delta = time.delta_secs();
+    if delta <= 0. {
+        return;
+    }
+
+    for (entity, mut controller, mut transform) in &mut players {
+        let filter = |hit: Entity| {
+            !std::iter::once(hit)
+                .chain(parents.iter_ancestors(hit))
+                .any(|e| e == entity || ignored.contains(e))
+        };
+        let ray_settings = RayCastSettings::default().with_filter(&filter);
+        let settings = controller.settings.clone();
+        let position = transform.translation;
+
+        // horizontal velocity towards the input, none while the editor camera has the controls
+        let input = if camera_manager.gameplay_input_enabled() {
+            im.get_motion3z(controller.move_action)
+        } else {
+            Vec3::ZERO
+        };
+        let wish = camera_manager.view_to_world(input).clamp_length_max(1.) * settings.max_speed;
+        let horizontal = Vec3::new(controller.velocity.x, 0., controller.velocity.z);
+        let rate = controller.control()
+            * if wish != Vec3::ZERO {
+                settings.acceleration
+            } else {
+                settings.deceleration
+            };
+        let mut horizontal = move_towards(horizontal, wish, rate * delta);
+
+        // slide along walls above step height
+        let mut step = horizontal * delta;
+        if let Ok(direction) = Dir3::new(step) {
+            let ray = Ray3d {
+                origin: position + UP * (settings.step_height + SKIN),
+                direction,
+            };
+            let reach = step.length() + settings.radius;
+            if let Some((_, hit)) = ray_cast.cast_ray(ray, &ray_settings).first() {
+                let normal = Vec3::new(hit.normal.x, 0., hit.normal.z).normalize_or_zero();
+                if hit.distance < reach && !controller.is_walkable(hit.normal) {
+                    let allowed = (hit.distance - settings.radius - SKIN).max(0.);
+                    let into_wall = step.dot(-normal).max(0.);
+                    step += normal * (into_wall - allowed.min(into_wall));
+                    horizontal -= normal * horizontal.dot(normal).min(0.);
+                }
+            }
+        }
+
+        // gravity, the ground probe below settles grounded characters
+        let mut vertical = controller.velocity.y;
+        if !controller.grounded {
+            let (min, max) = controller.vertical_speed_limits();
+            let acceleration = controller.lift() - controller.gravity();
+            vertical = (vertical + acceleration * delta).clamp(min, max);
+        }
+        let mut next = position + step + UP * (vertical * delta);
+
+        // ground probe from step height above, snapping down while grounded
+        let top = position.y.max(next.y) + settings.step_height;
+        let bottom = if controller.grounded {
+            next.y - settings.snap_distance
+        } else {
+            next.y
+        };
+        let ray = Ray3d {
+            origin: Vec3::new(next.x, top, next.z),
+            direction: -UP,
+        };
+        let ground = ray_cast
+            .cast_ray(ray, &ray_settings)
+            .first()
+            .map(|(_, hit)| (hit.point, hit.normal))
+            .filter(|(point, _)| vertical <= 0. && point.y >= bottom);
+
+        let was_grounded = controller.grounded;
+        controller.grounded = false;
+        match ground {
+            Some((point, normal)) if controller.is_walkable(normal) => {
+                if !was_grounded {
+                    ev_landed.send(jump::PlayerLanded {
+                        entity,
+                        impact_speed: -vertical,
+                    });
+                }
+                next.y = point.y;
+                vertical = 0.;
+                controller.grounded = true;
+                controller.ground_normal = normal;
+                controller.land();
+            }
+            Some((point, normal)) => {
+                // too steep to stand on, stay on the surface and slide off it
+                if point.y > position.y + SKIN {
+                    next.x = position.x;
+                    next.z = position.z;
+                } else {
+                    next.y = next.y.max(point.y);
+                }
+                horizontal += Vec3::new(normal.x, 0., normal.z) * settings.gravity * delta;
+                controller.ground_normal = Vec3::Y;
+            }
+            None => controller.ground_normal = Vec3::Y,
+        }
+
+        controller.velocity = Vec3::new(horizontal.x, vertical, horizontal.z);
+        transform.translation = next;
+
+        // turn to face the movement
+        if let Ok(facing) = Dir3::new(wish) {
+            let yaw = facing.x.atan2(facing.z) + settings.model_yaw_offset.to_radians();
+            let target = Quat::from_rotation_y(yaw);
+            let angle = transform.rotation.angle_between(target);
+            let max_step = settings.turn_speed.to_radians() * delta;
+            let t = if angle > max_step {
+                max_step / angle
+            } else {
+                1.
+            };
+            transform.rotation = transform.rotation.slerp(target, t);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOVE: input::Action = input::Action("move");
+
+    #[test]
+    fn move_towards_stops_at_the_target() {
+        let target = Vec3::new(3., 0., 4.);
+        let step = move_towards(Vec3::ZERO, target, 1.);
+        assert!(step.distance(Vec3::new(0.6, 0., 0.8)) < 1e-5);
+        assert_eq!(move_towards(Vec3::ZERO, target, 10.), target);
+    }
+
+    #[test]
+    fn slopes_up_to_max_slope_are_walkable() {
+        let controller = PlayerController::new(MOVE);
+        let slope = |degrees: f32| {
+            let radians = degrees.to_radians();
+            Vec3::new(radians.sin(), radians.cos(), 0.)
+        };
+        assert!(controller.is_walkable(Vec3::Y));
+        assert!(controller.is_walkable(slope(44.)));
+        assert!(!controller.is_walkable(slope(46.)));
+        assert!(!controller.is_walkable(Vec3::X));
+    }
+
+    #[test]
+    fn facing_undoes_the_model_yaw_offset() {
+        let mut controller = PlayerController::new(MOVE);
+        let transform = Transform::from_rotation(Quat::from_rotation_y(90f32.to_radians()));
+        assert!(controller.facing(&transform).distance(Vec3::X) < 1e-5);
+
+        controller.settings.model_yaw_offset = 90.;
+        assert!(controller.facing(&transform).distance(Vec3::Z) < 1e-5);
+    }
+
+    #[test]
+    fn upward_impulses_leave_the_ground() {
+        let mut controller = PlayerController::new(MOVE);
+        controller.grounded = true;
+        controller.add_impulse(Vec3::new(2., 0., 0.));
+        assert!(controller.is_grounded());
+        controller.add_impulse(Vec3::new(0., 3., 0.));
+        assert!(!controller.is_grounded());
+        assert_eq!(controller.velocity(), Vec3::new(2., 3., 0.));
+        assert_eq!(controller.horizontal_speed(), 2.);
+
+        controller.reset_motion();
+        assert_eq!(controller.velocity(), Vec3::ZERO);
+    }
+}
@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::PlayerController;
+use crate::{input_manager as input, isometric_camera::CameraManager, volume::BoxVolume};
+
+/**
+ * Tuning for hat ballooning, a held glide that slows falls while airborne
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GlideSettings {
+    // multiplier on gravity while gliding
+    pub gravity_scale: f32,
+    pub max_fall_speed: f32,
+    // rise speed cap while an updraft lifts the glide
+    pub max_rise_speed: f32,
+    // fraction of ground acceleration available for drifting
+    pub drift_control: f32,
+    // seconds of glide per airborne stretch, refilled on landing
+    pub max_duration: f32,
+}
+
+impl Default for GlideSettings {
+    fn default() -> Self {
+        Self {
+            gravity_scale: 0.15,
+            max_fall_speed: 2.,
+            max_rise_speed: 6.,
+            drift_control: 0.8,
+            max_duration: 3.,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct GlideState {
+    pub active: bool,
+    pub used: f32,
+    // upward acceleration from the updrafts the character is in
+    pub lift: f32,
+}
+
+/**
+ * Box volume, centered on the entity, that lifts gliding characters
+ */
+#[derive(Component, Debug, Clone)]
+pub struct Updraft {
+    pub volume: BoxVolume,
+    // upward acceleration in units per second squared
+    pub strength: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlideEnd {
+    Released,
+    Exhausted,
+    Landed,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct GlideStarted {
+    pub entity: Entity,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct GlideEnded {
+    pub entity: Entity,
+    pub reason: GlideEnd,
+}
+
+impl PlayerController {
+    pub fn with_glide(mut self, action: input::Action) -> Self {
+        self.glide_action = Some(action);
+        self
+    }
+
+    pub fn is_gliding(&self) -> bool {
+        self.glide_state.active
+    }
+
+    /**
+     * Seconds of glide left before landing again
+     */
+    pub fn glide_remaining(&self) -> f32 {
+        (self.glide.max_duration - self.glide_state.used).max(0.)
+    }
+
+    pub(super) fn lift(&self) -> f32 {
+        if self.glide_state.active {
+            self.glide_state.lift
+        } else {
+            0.
+        }
+    }
+
+    pub(super) fn vertical_speed_limits(&self) -> (f32, f32) {
+        if self.glide_state.active {
+            (-self.glide.max_fall_speed, self.glide.max_rise_speed)
+        } else {
+            (-self.settings.max_fall_speed, f32::INFINITY)
+        }
+    }
+}
+
+pub(super) fn glide_players(
+    time: Res<Time>,
+    im: Res<input::InputManager>,
+    camera_manager: Res<CameraManager>,
+    updrafts: Query<(&Updraft, &GlobalTransform)>,
+    mut players: Query<(Entity, &mut PlayerController, &Transform)>,
+    mut ev_started: EventWriter<GlideStarted>,
+    mut ev_ended: EventWriter<GlideEnded>,
+) {
+    let delta = time.delta_secs();
+    for (entity, mut controller, transform) in &mut players {
+        let Some(action) = controller.glide_action else {
+            continue;
+        };
+        let controller = &mut *controller;
+
+        let end = if controller.grounded {
+            controller.glide_state.used = 0.;
+            Some(GlideEnd::Landed)
+        } else if !camera_manager.gameplay_input_enabled() || !im.is_action_pressed(action) {
+            Some(GlideEnd::Released)
+        } else if controller.glide_remaining() <= 0. {
+            Some(GlideEnd::Exhausted)
+        } else {
+            None
+        };
+
+        let state = &mut controller.glide_state;
+        match end {
+            Some(reason) if state.active => {
+                state.active = false;
+                ev_ended.send(GlideEnded { entity, reason });
+            }
+            // the hat inflates once the character starts falling
+            None if !state.active && controller.velocity.y <= 0. => {
+                state.active = true;
+                ev_started.send(GlideStarted { entity });
+            }
+            _ => (),
+        }
+        if !state.active {
+            continue;
+        }
+
+        state.used += delta;
+        state.lift = updrafts
+            .iter()
+            .filter(|(updraft, global)| updraft.volume.contains(global, transform.translation))
+            .map(|(updraft, _)| updraft.strength)
+            .sum();
+    }
+}
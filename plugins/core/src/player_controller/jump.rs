@@ -0,0 +1,327 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::PlayerController;
+use crate::{input_manager as input, isometric_camera::CameraManager};
+
+/**
+ * Tuning for jumps and airborne movement, heights in world units and times in seconds
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct JumpSettings {
+    // apex height while the button is held
+    pub jump_height: f32,
+    // upward speed is multiplied by this when the button is released early
+    pub jump_cut: f32,
+    // grace period after walking off a ledge in which a jump still counts
+    pub coyote_time: f32,
+    // presses this long before landing jump on touchdown
+    pub buffer_time: f32,
+    // gravity multiplier while falling, for snappier arcs
+    pub fall_multiplier: f32,
+    // fraction of ground acceleration available in the air
+    pub air_control: f32,
+}
+
+impl Default for JumpSettings {
+    fn default() -> Self {
+        Self {
+            jump_height: 2.,
+            jump_cut: 0.5,
+            coyote_time: 0.12,
+            buffer_time: 0.1,
+            fall_multiplier: 1.8,
+            air_control: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct JumpState {
+    // seconds since the character last stood on the ground
+    pub since_grounded: f32,
+    pub buffered: f32,
+    // true from takeoff until landing, so coyote time can't be used twice
+    pub jumping: bool,
+}
+
+impl Default for JumpState {
+    fn default() -> Self {
+        Self {
+            // not grounded yet, so spawning or respawning in midair gives no coyote jump
+            since_grounded: f32::INFINITY,
+            buffered: 0.,
+            jumping: false,
+        }
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayerJumped {
+    pub entity: Entity,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayerLanded {
+    pub entity: Entity,
+    // downward speed at touchdown
+    pub impact_speed: f32,
+}
+
+impl PlayerController {
+    pub fn with_jump(mut self, action: input::Action) -> Self {
+        self.jump_action = Some(action);
+        self
+    }
+
+    pub fn is_jumping(&self) -> bool {
+        self.jump_state.jumping
+    }
+
+    pub(super) fn gravity(&self) -> f32 {
+        if self.is_gliding() {
+            self.settings.gravity * self.glide.gravity_scale
+        } else if self.velocity.y < 0. {
+            self.settings.gravity * self.jump.fall_multiplier
+        } else {
+            self.settings.gravity
+        }
+    }
+
+    pub(super) fn control(&self) -> f32 {
+        if self.grounded {
+            1.
+        } else if self.is_gliding() {
+            self.glide.drift_control
+        } else {
+            self.jump.air_control
+        }
+    }
+
+    pub(super) fn land(&mut self) {
+        self.jump_state.since_grounded = 0.;
+        self.jump_state.jumping = false;
+    }
+}
+
+pub(super) fn jump_players(
+    time: Res<Time>,
+    im: Res<input::InputManager>,
+    camera_manager: Res<CameraManager>,
+    mut players: Query<(Entity, &mut PlayerController)>,
+    mut ev_jumped: EventWriter<PlayerJumped>,
+) {
+    let delta = time.delta_secs();
+    let input_enabled = camera_manager.gameplay_input_enabled();
+    for (entity, mut controller) in &mut players {
+        let Some(action) = controller.jump_action else {
+            continue;
+        };
+        let controller = &mut *controller;
+        let state = &mut controller.jump_state;
+        if !controller.grounded {
+            state.since_grounded += delta;
+        }
+
+        if input_enabled && im.is_action_just_pressed(action) {
+            state.buffered = controller.jump.buffer_time.max(delta);
+        }
+        if input_enabled
+            && im.is_action_just_released(action)
+            && state.jumping
+            && controller.velocity.y > 0.
+        {
+            controller.velocity.y *= controller.jump.jump_cut;
+        }
+
+        let can_jump = !state.jumping && state.since_grounded <= controller.jump.coyote_time;
+        if state.buffered > 0. && can_jump {
+            controller.velocity.y =
+                (2. * controller.settings.gravity * controller.jump.jump_height).sqrt();
+            controller.grounded = false;
+            state.jumping = true;
+            state.buffered = 0.;
+            ev_jumped.send(PlayerJumped { entity });
+        }
+        state.buffered = (state.buffered - delta).max(0.);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        input::{
+            gamepad::GamepadEvent,
+            mouse::{AccumulatedMouseMotion, MouseButtonInput},
+        },
+        time::TimeUpdateStrategy,
+    };
+
+    use super::*;
+    use crate::{
+        input_manager::{button, InputManager, InputManagerPlugin},
+        isometric_camera::CameraMode,
+    };
+
+    const JUMP: input::Action = input::Action("jump");
+    const MOVE: input::Action = input::Action("move");
+
+    // 100ms frames, with a buffer longer than one frame
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputManagerPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )))
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .init_resource::<AccumulatedMouseMotion>()
+            .add_event::<MouseButtonInput>()
+            .add_event::<GamepadEvent>()
+            .init_resource::<CameraManager>()
+            .add_event::<PlayerJumped>()
+            .add_systems(Update, jump_players);
+        app.world_mut()
+            .resource_mut::<InputManager>()
+            .register_action_button(JUMP, vec![button::Variant::Keyboard(KeyCode::Space)]);
+        app.update();
+        app
+    }
+
+    fn spawn(app: &mut App, grounded: bool) -> Entity {
+        let mut controller = PlayerController::new(MOVE).with_jump(JUMP);
+        controller.jump = JumpSettings {
+            coyote_time: 0.15,
+            buffer_time: 0.25,
+            ..default()
+        };
+        if grounded {
+            controller.grounded = true;
+            controller.land();
+        }
+        app.world_mut().spawn(controller).id()
+    }
+
+    fn frame(app: &mut App, press: bool, release: bool) {
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.clear();
+        if press {
+            keyboard.press(KeyCode::Space);
+        }
+        if release {
+            keyboard.release(KeyCode::Space);
+        }
+        app.update();
+    }
+
+    fn controller(app: &mut App, entity: Entity) -> Mut<'_, PlayerController> {
+        app.world_mut().get_mut::<PlayerController>(entity).unwrap()
+    }
+
+    fn jumps(app: &mut App) -> usize {
+        app.world_mut()
+            .resource_mut::<Events<PlayerJumped>>()
+            .drain()
+            .count()
+    }
+
+    #[test]
+    fn releasing_early_cuts_the_jump() {
+        let mut app = app();
+        let entity = spawn(&mut app, true);
+
+        frame(&mut app, true, false);
+        assert_eq!(jumps(&mut app), 1);
+        let takeoff = controller(&mut app, entity).velocity.y;
+        assert!((takeoff - (2f32 * 20. * 2.).sqrt()).abs() < 1e-4);
+
+        frame(&mut app, false, true);
+        assert!((controller(&mut app, entity).velocity.y - takeoff * 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn releasing_while_falling_keeps_the_velocity() {
+        let mut app = app();
+        let entity = spawn(&mut app, true);
+        frame(&mut app, true, false);
+        controller(&mut app, entity).velocity.y = -3.;
+
+        frame(&mut app, false, true);
+        assert_eq!(controller(&mut app, entity).velocity.y, -3.);
+    }
+
+    #[test]
+    fn coyote_time_allows_a_late_jump() {
+        let mut app = app();
+        let entity = spawn(&mut app, true);
+        controller(&mut app, entity).grounded = false;
+
+        frame(&mut app, true, false);
+        assert_eq!(jumps(&mut app), 1);
+        assert!(controller(&mut app, entity).is_jumping());
+    }
+
+    #[test]
+    fn coyote_time_runs_out() {
+        let mut app = app();
+        let entity = spawn(&mut app, true);
+        controller(&mut app, entity).grounded = false;
+
+        frame(&mut app, false, false);
+        frame(&mut app, true, false);
+        assert_eq!(jumps(&mut app), 0);
+    }
+
+    #[test]
+    fn coyote_time_is_used_once() {
+        let mut app = app();
+        let entity = spawn(&mut app, true);
+        controller(&mut app, entity).grounded = false;
+
+        frame(&mut app, true, false);
+        assert_eq!(jumps(&mut app), 1);
+        frame(&mut app, false, true);
+        frame(&mut app, true, false);
+        assert_eq!(jumps(&mut app), 0);
+    }
+
+    #[test]
+    fn no_coyote_time_before_first_landing() {
+        let mut app = app();
+        spawn(&mut app, false);
+
+        frame(&mut app, true, false);
+        assert_eq!(jumps(&mut app), 0);
+    }
+
+    #[test]
+    fn buffered_press_jumps_on_landing() {
+        let mut app = app();
+        let entity = spawn(&mut app, false);
+
+        frame(&mut app, true, false);
+        assert_eq!(jumps(&mut app), 0);
+        {
+            let mut controller = controller(&mut app, entity);
+            controller.grounded = true;
+            controller.land();
+        }
+        frame(&mut app, false, false);
+        assert_eq!(jumps(&mut app), 1);
+    }
+
+    #[test]
+    fn editor_mode_ignores_the_jump_button() {
+        let mut app = app();
+        spawn(&mut app, true);
+        app.world_mut()
+            .resource_mut::<CameraManager>()
+            .set_mode(CameraMode::EDITOR);
+
+        frame(&mut app, true, false);
+        assert_eq!(jumps(&mut app), 0);
+    }
+}
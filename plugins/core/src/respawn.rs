@@ -0,0 +1,298 @@
+use bevy::prelude::*;
+
+use crate::{health::Died, player_controller::PlayerController, volume::BoxVolume};
+
+pub mod fade;
+
+/**
+ * Checkpoints, kill volumes and the respawn fade. Characters also respawn on
+ * Died, registered by the health plugin
+ */
+pub struct RespawnPlugin;
+
+impl Plugin for RespawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RespawnSettings>()
+            .init_resource::<fade::ScreenFade>()
+            .add_event::<CheckpointActivated>()
+            .add_event::<RespawnRequested>()
+            .add_event::<PlayerRespawned>()
+            .add_event::<WorldReset>()
+            .add_systems(Startup, fade::spawn_fade)
+            .add_systems(
+                Update,
+                (
+                    capture_initial_state,
+                    pass_checkpoints,
+                    detect_kills,
+                    respawn_dead,
+                    fade::start_fades,
+                    fade::update_fades,
+                    respawn_players,
+                    reset_world,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/**
+ * Timing of the respawn fade and the bounds of the playable world
+ */
+#[derive(Resource, Debug, Clone)]
+pub struct RespawnSettings {
+    pub fade_out: f32,
+    // seconds spent fully faded while the world resets
+    pub hold: f32,
+    pub fade_in: f32,
+    pub fade_color: Color,
+    // min and max corner of the sub-world cube, leaving it kills
+    pub world_bounds: Option<(Vec3, Vec3)>,
+}
+
+impl Default for RespawnSettings {
+    fn default() -> Self {
+        Self {
+            fade_out: 0.4,
+            hold: 0.3,
+            fade_in: 0.5,
+            fade_color: Color::BLACK,
+            world_bounds: None,
+        }
+    }
+}
+
+/**
+ * Character sent back to its last active respawn point, starting at
+ * where it was spawned
+ */
+#[derive(Component, Debug, Clone, Default)]
+pub struct Respawnable {
+    point: Option<Transform>,
+    checkpoint: Option<String>,
+    respawning: bool,
+}
+
+impl Respawnable {
+    pub fn checkpoint(&self) -> Option<&str> {
+        self.checkpoint.as_deref()
+    }
+
+    pub fn respawn_point(&self) -> Option<&Transform> {
+        self.point.as_ref()
+    }
+
+    pub fn is_respawning(&self) -> bool {
+        self.respawning
+    }
+}
+
+/**
+ * Box volume, centered on the entity, activating its respawn point when
+ * passed. Respawns face the checkpoint's forward
+ */
+#[derive(Component, Debug, Clone)]
+pub struct Checkpoint {
+    pub id: String,
+    pub volume: BoxVolume,
+    // respawn point relative to the checkpoint
+    pub offset: Vec3,
+}
+
+/**
+ * Box volume, centered on the entity, respawning characters entering it
+ */
+#[derive(Component, Debug, Clone)]
+pub struct KillVolume {
+    pub volume: BoxVolume,
+}
+
+/**
+ * World entity put back where it started on every respawn, e.g. a pushed crate
+ */
+#[derive(Component, Debug, Clone, Default)]
+pub struct ResetOnRespawn {
+    initial: Option<Transform>,
+}
+
+/**
+ * Transient entity removed on every respawn, e.g. a thrown bomb
+ */
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct DespawnOnRespawn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RespawnCause {
+    KillVolume,
+    OutOfBounds,
+    // Health ran out
+    Died,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct CheckpointActivated {
+    pub entity: Entity,
+    pub checkpoint: String,
+}
+
+/**
+ * Starts the fade to a respawn, can also be sent by game code
+ */
+#[derive(Event, Debug, Clone, Copy)]
+pub struct RespawnRequested {
+    pub entity: Entity,
+    pub cause: RespawnCause,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct PlayerRespawned {
+    pub entity: Entity,
+    pub checkpoint: Option<String>,
+}
+
+/**
+ * Sent while the screen is faded out, for game state beyond ResetOnRespawn
+ * and DespawnOnRespawn to reset along
+ */
+#[derive(Event, Debug, Clone, Copy)]
+pub struct WorldReset;
+
+fn capture_initial_state(
+    mut players: Query<(&mut Respawnable, &Transform), Added<Respawnable>>,
+    mut resets: Query<(&mut ResetOnRespawn, &Transform), Added<ResetOnRespawn>>,
+) {
+    for (mut respawnable, transform) in &mut players {
+        respawnable.point.get_or_insert(*transform);
+    }
+    for (mut reset, transform) in &mut resets {
+        reset.initial.get_or_insert(*transform);
+    }
+}
+
+fn pass_checkpoints(
+    checkpoints: Query<(&Checkpoint, &GlobalTransform)>,
+    mut players: Query<(Entity, &mut Respawnable, &Transform)>,
+    mut ev_activated: EventWriter<CheckpointActivated>,
+) {
+    for (entity, mut respawnable, transform) in &mut players {
+        if respawnable.respawning {
+            continue;
+        }
+        let Some((checkpoint, global)) = checkpoints
+            .iter()
+            .find(|(checkpoint, global)| checkpoint.volume.contains(global, transform.translation))
+        else {
+            continue;
+        };
+        if respawnable.checkpoint.as_ref() == Some(&checkpoint.id) {
+            continue;
+        }
+        let (_, rotation, translation) = global.to_scale_rotation_translation();
+        respawnable.point = Some(
+            Transform::from_translation(translation + rotation * checkpoint.offset)
+                .with_rotation(rotation),
+        );
+        respawnable.checkpoint = Some(checkpoint.id.clone());
+        ev_activated.send(CheckpointActivated {
+            entity,
+            checkpoint: checkpoint.id.clone(),
+        });
+    }
+}
+
+fn detect_kills(
+    settings: Res<RespawnSettings>,
+    kill_volumes: Query<(&KillVolume, &GlobalTransform)>,
+    players: Query<(Entity, &Respawnable, &Transform)>,
+    mut ev_requested: EventWriter<RespawnRequested>,
+) {
+    for (entity, respawnable, transform) in &players {
+        if respawnable.respawning {
+            continue;
+        }
+        let position = transform.translation;
+        let out_of_bounds = settings
+            .world_bounds
+            .is_some_and(|(min, max)| position.cmplt(min).any() || position.cmpgt(max).any());
+        let cause = if out_of_bounds {
+            RespawnCause::OutOfBounds
+        } else if kill_volumes
+            .iter()
+            .any(|(kill_volume, global)| kill_volume.volume.contains(global, position))
+        {
+            RespawnCause::KillVolume
+        } else {
+            continue;
+        };
+        ev_requested.send(RespawnRequested { entity, cause });
+    }
+}
+
+fn respawn_dead(
+    mut ev_died: EventReader<Died>,
+    players: Query<(), With<Respawnable>>,
+    mut ev_requested: EventWriter<RespawnRequested>,
+) {
+    for died in ev_died.read() {
+        if players.contains(died.entity) {
+            ev_requested.send(RespawnRequested {
+                entity: died.entity,
+                cause: RespawnCause::Died,
+            });
+        }
+    }
+}
+
+/**
+ * Moves the faded out characters to their respawn points
+ */
+fn respawn_players(
+    mut screen_fade: ResMut<fade::ScreenFade>,
+    mut players: Query<(
+        &mut Respawnable,
+        &mut Transform,
+        Option<&mut PlayerController>,
+    )>,
+    mut ev_respawned: EventWriter<PlayerRespawned>,
+    mut ev_reset: EventWriter<WorldReset>,
+) {
+    let Some(entities) = screen_fade.take_faded_out() else {
+        return;
+    };
+    for entity in entities {
+        let Ok((respawnable, mut transform, controller)) = players.get_mut(entity) else {
+            continue;
+        };
+        if let Some(point) = respawnable.point {
+            transform.translation = point.translation;
+            transform.rotation = point.rotation;
+        }
+        if let Some(mut controller) = controller {
+            controller.reset_motion();
+        }
+        ev_respawned.send(PlayerRespawned {
+            entity,
+            checkpoint: respawnable.checkpoint.clone(),
+        });
+    }
+    ev_reset.send(WorldReset);
+}
+
+fn reset_world(
+    mut commands: Commands,
+    mut ev_reset: EventReader<WorldReset>,
+    mut resets: Query<(&ResetOnRespawn, &mut Transform)>,
+    despawns: Query<Entity, With<DespawnOnRespawn>>,
+) {
+    if ev_reset.read().count() == 0 {
+        return;
+    }
+    for (reset, mut transform) in &mut resets {
+        if let Some(initial) = reset.initial {
+            *transform = initial;
+        }
+    }
+    for entity in &despawns {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+
+use super::{RespawnRequested, RespawnSettings, Respawnable};
+
+/**
+ * Full screen node faded over the game while respawning
+ */
+#[derive(Component)]
+pub struct FadeOverlay;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FadePhase {
+    #[default]
+    Idle,
+    Out,
+    // fully faded, respawns happen on entering it
+    Hold,
+    In,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct ScreenFade {
+    phase: FadePhase,
+    elapsed: f32,
+    // characters waiting for the screen to fade out
+    pending: Vec<Entity>,
+    // characters respawned, waiting for the screen to fade back in
+    returning: Vec<Entity>,
+    faded_out: bool,
+}
+
+impl ScreenFade {
+    pub fn phase(&self) -> FadePhase {
+        self.phase
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.phase != FadePhase::Idle
+    }
+
+    /**
+     * The characters to respawn, once, on the frame the screen is fully faded
+     */
+    pub(super) fn take_faded_out(&mut self) -> Option<Vec<Entity>> {
+        if !std::mem::take(&mut self.faded_out) {
+            return None;
+        }
+        let entities = std::mem::take(&mut self.pending);
+        self.returning.extend(entities.iter().copied());
+        Some(entities)
+    }
+}
+
+fn progress(elapsed: f32, duration: f32) -> f32 {
+    if duration > 0. {
+        (elapsed / duration).min(1.)
+    } else {
+        1.
+    }
+}
+
+pub(super) fn spawn_fade(mut commands: Commands) {
+    commands.spawn((
+        FadeOverlay,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            ..default()
+        },
+        BackgroundColor(Color::NONE),
+        GlobalZIndex(i32::MAX),
+        PickingBehavior::IGNORE,
+    ));
+}
+
+pub(super) fn start_fades(
+    settings: Res<RespawnSettings>,
+    mut ev_requested: EventReader<RespawnRequested>,
+    mut screen_fade: ResMut<ScreenFade>,
+    mut players: Query<&mut Respawnable>,
+) {
+    for event in ev_requested.read() {
+        let Ok(mut respawnable) = players.get_mut(event.entity) else {
+            continue;
+        };
+        if respawnable.respawning {
+            continue;
+        }
+        respawnable.respawning = true;
+        screen_fade.pending.push(event.entity);
+        match screen_fade.phase {
+            FadePhase::Idle => {
+                screen_fade.phase = FadePhase::Out;
+                screen_fade.elapsed = 0.;
+            }
+            // turns around from the current fade level
+            FadePhase::In => {
+                let alpha = 1. - progress(screen_fade.elapsed, settings.fade_in);
+                screen_fade.phase = FadePhase::Out;
+                screen_fade.elapsed = alpha * settings.fade_out;
+            }
+            // already dark, respawned right away
+            FadePhase::Hold => screen_fade.faded_out = true,
+            FadePhase::Out => (),
+        }
+    }
+}
+
+pub(super) fn update_fades(
+    time: Res<Time>,
+    settings: Res<RespawnSettings>,
+    mut screen_fade: ResMut<ScreenFade>,
+    mut overlays: Query<&mut BackgroundColor, With<FadeOverlay>>,
+    mut players: Query<&mut Respawnable>,
+) {
+    if !screen_fade.is_fading() {
+        return;
+    }
+    let screen_fade = &mut *screen_fade;
+    screen_fade.elapsed += time.delta_secs();
+    let alpha = match screen_fade.phase {
+        FadePhase::Idle => 0.,
+        FadePhase::Out => {
+            let t = progress(screen_fade.elapsed, settings.fade_out);
+            if t >= 1. {
+                screen_fade.phase = FadePhase::Hold;
+                screen_fade.elapsed = 0.;
+                screen_fade.faded_out = true;
+            }
+            t
+        }
+        FadePhase::Hold => {
+            if screen_fade.elapsed >= settings.hold {
+                screen_fade.phase = FadePhase::In;
+                screen_fade.elapsed = 0.;
+            }
+            1.
+        }
+        FadePhase::In => {
+            let t = progress(screen_fade.elapsed, settings.fade_in);
+            if t >= 1. {
+                screen_fade.phase = FadePhase::Idle;
+                for entity in screen_fade.returning.drain(..) {
+                    if let Ok(mut respawnable) = players.get_mut(entity) {
+                        respawnable.respawning = false;
+                    }
+                }
+            }
+            1. - t
+        }
+    };
+    for mut background in &mut overlays {
+        background.0 = settings.fade_color.with_alpha(alpha);
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use crate::{
+    character_animation::CharacterAnimator, input_manager as input,
+    isometric_camera::CameraManager, player_controller::PlayerController,
+    ron_asset::RonAssetPlugin,
+};
+
+pub mod effects;
+
+pub struct SkillsPlugin;
+
+impl Plugin for SkillsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<SkillBook>::new(&["skills.ron"]))
+            .add_event::<SkillCastStarted>()
+            .add_event::<SkillActivated>()
+            .add_event::<SkillRejected>()
+            .add_event::<effects::Rotted>()
+            .add_event::<effects::Bloomed>()
+            .add_systems(
+                Update,
+                (
+                    activate_skills,
+                    finish_casts,
+                    effects::apply_effects,
+                    effects::rot,
+                    effects::bloom,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/**
+ * Where a skill lands, distances in world units
+ */
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum Targeting {
+    // centered on the caster
+    Caster,
+    // in front of the caster
+    Forward { range: f32 },
+    // under the cursor on the caster's ground plane, in front of the caster without a cursor
+    Cursor { range: f32 },
+}
+
+/**
+ * What an activated skill does to the tagged entities in its area,
+ * Custom skills are left to the game's own SkillActivated readers
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub enum SkillEffect {
+    Rot,
+    Bloom,
+    Custom(String),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SkillDef {
+    pub cooldown: f32,
+    // energy drawn from the caster on activation
+    #[serde(default)]
+    pub cost: f32,
+    // seconds between pressing and the effect, the caster can't start other casts meanwhile
+    #[serde(default)]
+    pub cast_time: f32,
+    pub targeting: Targeting,
+    // area around the target point the effect reaches
+    pub radius: f32,
+    pub effect: SkillEffect,
+    // character animation state played on cast, defaults to the skill name
+    #[serde(default)]
+    pub animation: Option<String>,
+}
+
+/**
+ * Skill definitions by name, loaded from `*.skills.ron` files
+ */
+#[derive(Asset, TypePath, Debug, Clone, Default, Deserialize)]
+pub struct SkillBook {
+    pub skills: BTreeMap<String, SkillDef>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SkillSlot {
+    pub action: input::Action,
+    pub skill: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Cast {
+    skill: String,
+    remaining: f32,
+}
+
+/**
+ * Skills a character has unlocked, the slots they are bound to and the
+ * energy they are paid with
+ */
+#[derive(Component, Debug, Clone)]
+pub struct SkillCaster {
+    pub book: Handle<SkillBook>,
+    pub slots: Vec<SkillSlot>,
+    pub max_energy: f32,
+    // energy per second
+    pub energy_regen: f32,
+    energy: f32,
+    unlocked: BTreeSet<String>,
+    cooldowns: HashMap<String, f32>,
+    cast: Option<Cast>,
+}
+
+impl SkillCaster {
+    pub fn new(book: Handle<SkillBook>) -> Self {
+        Self {
+            book,
+            slots: Vec::new(),
+            max_energy: 100.,
+            energy_regen: 10.,
+            energy: 100.,
+            unlocked: BTreeSet::new(),
+            cooldowns: HashMap::default(),
+            cast: None,
+        }
+    }
+
+    pub fn with_slot(mut self, action: input::Action, skill: Option<&str>) -> Self {
+        self.slots.push(SkillSlot {
+            action,
+            skill: skill.map(str::to_string),
+        });
+        self
+    }
+
+    pub fn unlock(&mut self, skill: &str) {
+        self.unlocked.insert(skill.to_string());
+    }
+
+    pub fn lock(&mut self, skill: &str) {
+        self.unlocked.remove(skill);
+    }
+
+    pub fn is_unlocked(&self, skill: &str) -> bool {
+        self.unlocked.contains(skill)
+    }
+
+    pub fn unlocked(&self) -> impl Iterator<Item = &str> {
+        self.unlocked.iter().map(String::as_str)
+    }
+
+    /**
+     * Binds a skill to a slot, returns false if the slot doesn't exist
+     */
+    pub fn assign(&mut self, slot: usize, skill: Option<&str>) -> bool {
+        let Some(slot) = self.slots.get_mut(slot) else {
+            return false;
+        };
+        slot.skill = skill.map(str::to_string);
+        true
+    }
+
+    pub fn energy(&self) -> f32 {
+        self.energy
+    }
+
+    pub fn cooldown_remaining(&self, skill: &str) -> f32 {
+        self.cooldowns.get(skill).copied().unwrap_or(0.)
+    }
+
+    pub fn casting(&self) -> Option<&str> {
+        self.cast.as_ref().map(|cast| cast.skill.as_str())
+    }
+
+    /**
+     * Starts casting `skill` if the caster is able to
+     */
+    pub fn try_cast(&mut self, skill: &str, def: &SkillDef) -> Result<(), SkillError> {
+        if !self.is_unlocked(skill) {
+            return Err(SkillError::Locked);
+        }
+        if self.cast.is_some() {
+            return Err(SkillError::Busy);
+        }
+        if self.cooldown_remaining(skill) > 0. {
+            return Err(SkillError::Cooldown);
+        }
+        if self.energy < def.cost {
+            return Err(SkillError::Energy);
+        }
+        self.energy -= def.cost;
+        self.cooldowns.insert(skill.to_string(), def.cooldown);
+        self.cast = Some(Cast {
+            skill: skill.to_string(),
+            remaining: def.cast_time,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkillError {
+    Locked,
+    Busy,
+    Cooldown,
+    Energy,
+    Unknown,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct SkillCastStarted {
+    pub caster: Entity,
+    pub skill: String,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct SkillActivated {
+    pub caster: Entity,
+    pub skill: String,
+    pub effect: SkillEffect,
+    pub point: Vec3,
+    pub radius: f32,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct SkillRejected {
+    pub caster: Entity,
+    pub skill: String,
+    pub reason: SkillError,
+}
+
+fn activate_skills(
+    time: Res<Time>,
+    im: Res<input::InputManager>,
+    camera_manager: Res<CameraManager>,
+    books: Res<Assets<SkillBook>>,
+    mut casters: Query<(Entity, &mut SkillCaster, Option<&mut CharacterAnimator>)>,
+    mut ev_started: EventWriter<SkillCastStarted>,
+    mut ev_rejected: EventWriter<SkillRejected>,
+) {
+    let delta = time.delta_secs();
+    let input_enabled = camera_manager.gameplay_input_enabled();
+    for (entity, mut caster, animator) in &mut casters {
+        let caster = &mut *caster;
+        caster.energy = (caster.energy + caster.energy_regen * delta).min(caster.max_energy);
+        caster.cooldowns.retain(|_, remaining| {
+            *remaining -= delta;
+            *remaining > 0.
+        });
+
+        let Some(skill) = caster
+            .slots
+            .iter()
+            .find(|slot| input_enabled && im.is_action_just_pressed(slot.action))
+            .and_then(|slot| slot.skill.clone())
+        else {
+            continue;
+        };
+        let result = match books.get(&caster.book).and_then(|b| b.skills.get(&skill)) {
+            Some(def) => caster.try_cast(&skill, def).map(|_| def),
+            None => Err(SkillError::Unknown),
+        };
+        match result {
+            Ok(def) => {
+                if let Some(mut animator) = animator {
+                    animator.play_cast(def.animation.as_deref().unwrap_or(&skill));
+                }
+                ev_started.send(SkillCastStarted {
+                    caster: entity,
+                    skill,
+                });
+            }
+            Err(reason) => {
+                ev_rejected.send(SkillRejected {
+                    caster: entity,
+                    skill,
+                    reason,
+                });
+            }
+        }
+    }
+}
+
+fn target_point(
+    targeting: Targeting,
+    transform: &Transform,
+    controller: Option<&PlayerController>,
+    camera_manager: &CameraManager,
+) -> Vec3 {
+    let position = transform.translation;
+    let forward = controller.map_or(transform.forward().as_vec3(), |c| c.facing(transform));
+    match targeting {
+        Targeting::Caster => position,
+        Targeting::Forward { range } => position + forward * range,
+        Targeting::Cursor { range } => camera_manager
+            .cursor_position()
+            .and_then(|cursor| camera_manager.screen_to_plane(cursor, position, Dir3::Y))
+            .map(|point| position + (point - position).clamp_length_max(range))
+            .unwrap_or(position + forward * range),
+    }
+}
+
+fn finish_casts(
+    time: Res<Time>,
+    books: Res<Assets<SkillBook>>,
+    camera_manager: Res<CameraManager>,
+    mut casters: Query<(
+        Entity,
+        &mut SkillCaster,
+        &Transform,
+        Option<&PlayerController>,
+    )>,
+    mut ev_activated: EventWriter<SkillActivated>,
+) {
+    let delta = time.delta_secs();
+    for (entity, mut caster, transform, controller) in &mut casters {
+        let Some(cast) = &mut caster.cast else {
+            continue;
+        };
+        cast.remaining -= delta;
+        if cast.remaining > 0. {
+            continue;
+        }
+        let Some(cast) = caster.cast.take() else {
+            continue;
+        };
+        let Some(def) = books
+            .get(&caster.book)
+            .and_then(|b| b.skills.get(&cast.skill))
+        else {
+            continue;
+        };
+        ev_activated.send(SkillActivated {
+            caster: entity,
+            effect: def.effect.clone(),
+            point: target_point(def.targeting, transform, controller, &camera_manager),
+            radius: def.radius,
+            skill: cast.skill,
+        });
+    }
+}
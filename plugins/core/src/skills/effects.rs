@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+
+use super::{SkillActivated, SkillEffect};
+use crate::easing::Easing;
+
+/**
+ * World entity removed by the rot skill, e.g. a tree blocking a path
+ */
+#[derive(Component, Debug, Clone)]
+pub struct Rottable {
+    // seconds from hit to gone
+    pub rot_time: f32,
+}
+
+impl Default for Rottable {
+    fn default() -> Self {
+        Self { rot_time: 1. }
+    }
+}
+
+/**
+ * World entity grown by the bloom skill, e.g. a sprout becoming a platform.
+ * It is spawned at its sprout scale and grows to `grown_scale`
+ */
+#[derive(Component, Debug, Clone)]
+pub struct Bloomable {
+    pub grown_scale: Vec3,
+    pub grow_time: f32,
+}
+
+impl Default for Bloomable {
+    fn default() -> Self {
+        Self {
+            grown_scale: Vec3::ONE,
+            grow_time: 1.,
+        }
+    }
+}
+
+#[derive(Component, Default)]
+pub struct Rotting {
+    elapsed: f32,
+    // scale when the effect hit, taken on the first update
+    from: Option<Vec3>,
+}
+
+#[derive(Component, Default)]
+pub struct Blooming {
+    elapsed: f32,
+    from: Option<Vec3>,
+}
+
+/**
+ * Sent when a rotted entity is gone, right before it is despawned
+ */
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Rotted(pub Entity);
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Bloomed(pub Entity);
+
+#[allow(clippy::type_complexity)]
+pub(super) fn apply_effects(
+    mut commands: Commands,
+    mut ev_activated: EventReader<SkillActivated>,
+    rottables: Query<(Entity, &GlobalTransform), (With<Rottable>, Without<Rotting>)>,
+    bloomables: Query<(Entity, &GlobalTransform), (With<Bloomable>, Without<Blooming>)>,
+) {
+    // targets may be despawned by other systems before the commands apply
+    for event in ev_activated.read() {
+        let in_reach =
+            |global: &GlobalTransform| global.translation().distance(event.point) <= event.radius;
+        match event.effect {
+            SkillEffect::Rot => {
+                for (entity, global) in &rottables {
+                    if in_reach(global) {
+                        commands.entity(entity).try_insert(Rotting::default());
+                    }
+                }
+            }
+            SkillEffect::Bloom => {
+                for (entity, global) in &bloomables {
+                    if in_reach(global) {
+                        commands.entity(entity).try_insert(Blooming::default());
+                    }
+                }
+            }
+            SkillEffect::Custom(_) => (),
+        }
+    }
+}
+
+pub(super) fn rot(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut rotting: Query<(Entity, &Rottable, &mut Rotting, &mut Transform)>,
+    mut ev_rotted: EventWriter<Rotted>,
+) {
+    for (entity, rottable, mut state, mut transform) in &mut rotting {
+        let from = *state.from.get_or_insert(transform.scale);
+        state.elapsed += time.delta_secs();
+        let t = if rottable.rot_time > 0. {
+            state.elapsed / rottable.rot_time
+        } else {
+            1.
+        };
+        if t >= 1. {
+            ev_rotted.send(Rotted(entity));
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        transform.scale = from * (1. - Easing::QuadraticIn.sample(t));
+    }
+}
+
+pub(super) fn bloom(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut blooming: Query<(Entity, &Bloomable, &mut Blooming, &mut Transform)>,
+    mut ev_bloomed: EventWriter<Bloomed>,
+) {
+    for (entity, bloomable, mut state, mut transform) in &mut blooming {
+        let from = *state.from.get_or_insert(transform.scale);
+        state.elapsed += time.delta_secs();
+        let t = if bloomable.grow_time > 0. {
+            state.elapsed / bloomable.grow_time
+        } else {
+            1.
+        };
+        transform.scale = from.lerp(bloomable.grown_scale, Easing::QuadraticOut.sample(t));
+        if t >= 1. {
+            ev_bloomed.send(Bloomed(entity));
+            commands.entity(entity).remove::<(Bloomable, Blooming)>();
+        }
+    }
+}
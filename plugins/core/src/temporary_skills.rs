@@ -0,0 +1,375 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    input_manager as input, isometric_camera::CameraManager, ron_asset::RonAssetPlugin,
+    skills::SkillCaster,
+};
+
+pub mod bombs;
+pub mod hud;
+
+pub struct TemporarySkillsPlugin {
+    // shows charges and running spells of the first inventory in a corner of the screen
+    pub hud: bool,
+}
+
+impl Default for TemporarySkillsPlugin {
+    fn default() -> Self {
+        Self { hud: true }
+    }
+}
+
+impl Plugin for TemporarySkillsPlugin {
+    fn build(&self, app: &mut App) {
+        if self.hud {
+            app.add_systems(Startup, hud::spawn_hud)
+                .add_systems(Update, hud::update_hud.after(expire_spells));
+        }
+        app.add_plugins(RonAssetPlugin::<TemporarySkillBook>::new(&[
+            "temporary.ron",
+        ]))
+        .add_event::<TemporarySkillPickedUp>()
+        .add_event::<TemporarySkillUsed>()
+        .add_event::<TemporarySkillExpired>()
+        .add_event::<bombs::Explosion>()
+        .add_event::<bombs::Exploded>()
+        .add_systems(Startup, bombs::setup_bomb_assets)
+        .add_systems(
+            Update,
+            (
+                pick_up,
+                use_temporary_skills,
+                expire_spells,
+                bombs::preview_throws,
+                bombs::throw_bombs,
+                bombs::fly_bombs,
+                bombs::explode_bombs,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum TemporarySkillKind {
+    // thrown along an arc while the use button is released, exploding after its fuse
+    Bomb(bombs::BombDef),
+    // unlocks a skill of the SkillCaster until the duration runs out
+    Spell { skill: String, duration: f32 },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TemporarySkillDef {
+    pub kind: TemporarySkillKind,
+    pub max_charges: u32,
+}
+
+/**
+ * Consumable skills by name, loaded from `*.temporary.ron` files
+ */
+#[derive(Asset, TypePath, Debug, Clone, Default, Deserialize)]
+pub struct TemporarySkillBook {
+    pub skills: BTreeMap<String, TemporarySkillDef>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ActiveSpell {
+    pub name: String,
+    pub skill: String,
+    pub remaining: f32,
+    // false if the caster had the skill before, so it isn't locked on expiry
+    granted: bool,
+}
+
+/**
+ * Inventory of consumable skills, charges are gained from pickups
+ */
+#[derive(Component, Debug, Clone)]
+pub struct TemporarySkills {
+    pub book: Handle<TemporarySkillBook>,
+    // held to aim bombs, pressed to cast spells
+    pub use_action: input::Action,
+    pub cycle_action: Option<input::Action>,
+    charges: BTreeMap<String, u32>,
+    selected: Option<String>,
+    spells: Vec<ActiveSpell>,
+    aiming: bool,
+    // released throws, spawned by the bomb systems
+    throws: Vec<bombs::BombDef>,
+}
+
+impl TemporarySkills {
+    pub fn new(book: Handle<TemporarySkillBook>, use_action: input::Action) -> Self {
+        Self {
+            book,
+            use_action,
+            cycle_action: None,
+            charges: BTreeMap::new(),
+            selected: None,
+            spells: Vec::new(),
+            aiming: false,
+            throws: Vec::new(),
+        }
+    }
+
+    pub fn with_cycle(mut self, action: input::Action) -> Self {
+        self.cycle_action = Some(action);
+        self
+    }
+
+    pub fn charges(&self, name: &str) -> u32 {
+        self.charges.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn iter_charges(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.charges
+            .iter()
+            .map(|(name, count)| (name.as_str(), *count))
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.selected.as_deref()
+    }
+
+    pub fn active_spells(&self) -> &[ActiveSpell] {
+        &self.spells
+    }
+
+    pub fn is_aiming(&self) -> bool {
+        self.aiming
+    }
+
+    /**
+     * Adds charges up to the skill's maximum, returns how many were taken
+     */
+    pub fn add_charges(&mut self, name: &str, count: u32, max_charges: u32) -> u32 {
+        let taken = count.min(max_charges.saturating_sub(self.charges(name)));
+        if taken == 0 {
+            return 0;
+        }
+        *self.charges.entry(name.to_string()).or_default() += taken;
+        if self.selected.is_none() {
+            self.selected = Some(name.to_string());
+        }
+        taken
+    }
+
+    fn cycle(&mut self) {
+        let names: Vec<&String> = self.charges.keys().collect();
+        if names.is_empty() {
+            return;
+        }
+        let next = match &self.selected {
+            Some(selected) => names
+                .iter()
+                .position(|name| *name == selected)
+                .map_or(0, |i| (i + 1) % names.len()),
+            None => 0,
+        };
+        self.selected = Some(names[next].clone());
+    }
+
+    /**
+     * Takes one charge, returns true if the skill ran out
+     */
+    fn consume(&mut self, name: &str) -> bool {
+        let Some(charges) = self.charges.get_mut(name) else {
+            return false;
+        };
+        *charges = charges.saturating_sub(1);
+        if *charges > 0 {
+            return false;
+        }
+        self.charges.remove(name);
+        if self.selected.as_deref() == Some(name) {
+            self.selected = self.charges.keys().next().cloned();
+        }
+        true
+    }
+}
+
+/**
+ * Grants charges to a TemporarySkills inventory coming within `radius`
+ */
+#[derive(Component, Debug, Clone)]
+pub struct TemporarySkillPickup {
+    pub skill: String,
+    pub charges: u32,
+    pub radius: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpiryReason {
+    // the last charge was used, and for a spell its last cast has run out
+    Depleted,
+    // a spell's duration ran out with charges left
+    TimedOut,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct TemporarySkillPickedUp {
+    pub entity: Entity,
+    pub skill: String,
+    pub charges: u32,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct TemporarySkillUsed {
+    pub entity: Entity,
+    pub skill: String,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct TemporarySkillExpired {
+    pub entity: Entity,
+    pub skill: String,
+    pub reason: ExpiryReason,
+}
+
+fn pick_up(
+    mut commands: Commands,
+    books: Res<Assets<TemporarySkillBook>>,
+    pickups: Query<(Entity, &TemporarySkillPickup, &GlobalTransform)>,
+    mut inventories: Query<(Entity, &mut TemporarySkills, &GlobalTransform)>,
+    mut ev_picked_up: EventWriter<TemporarySkillPickedUp>,
+) {
+    for (pickup_entity, pickup, pickup_transform) in &pickups {
+        for (entity, mut inventory, transform) in &mut inventories {
+            let distance = transform
+                .translation()
+                .distance(pickup_transform.translation());
+            if distance > pickup.radius {
+                continue;
+            }
+            let Some(def) = books
+                .get(&inventory.book)
+                .and_then(|b| b.skills.get(&pickup.skill))
+            else {
+                continue;
+            };
+            let taken = inventory.add_charges(&pickup.skill, pickup.charges, def.max_charges);
+            if taken == 0 {
+                continue;
+            }
+            ev_picked_up.send(TemporarySkillPickedUp {
+                entity,
+                skill: pickup.skill.clone(),
+                charges: taken,
+            });
+            commands.entity(pickup_entity).despawn_recursive();
+            break;
+        }
+    }
+}
+
+fn use_temporary_skills(
+    im: Res<input::InputManager>,
+    camera_manager: Res<CameraManager>,
+    books: Res<Assets<TemporarySkillBook>>,
+    mut inventories: Query<(Entity, &mut TemporarySkills, Option<&mut SkillCaster>)>,
+    mut ev_used: EventWriter<TemporarySkillUsed>,
+    mut ev_expired: EventWriter<TemporarySkillExpired>,
+) {
+    if !camera_manager.gameplay_input_enabled() {
+        // drop a held aim instead of throwing once the editor camera takes over
+        for (_, mut inventory, _) in &mut inventories {
+            inventory.aiming = false;
+        }
+        return;
+    }
+    for (entity, mut inventory, caster) in &mut inventories {
+        if inventory
+            .cycle_action
+            .is_some_and(|action| im.is_action_just_pressed(action))
+        {
+            inventory.cycle();
+        }
+        let Some(name) = inventory.selected.clone() else {
+            inventory.aiming = false;
+            continue;
+        };
+        let Some(def) = books.get(&inventory.book).and_then(|b| b.skills.get(&name)) else {
+            continue;
+        };
+
+        let used = match &def.kind {
+            TemporarySkillKind::Bomb(bomb) => {
+                let released = inventory.aiming && !im.is_action_pressed(inventory.use_action);
+                inventory.aiming = im.is_action_pressed(inventory.use_action);
+                if released {
+                    inventory.throws.push(bomb.clone());
+                }
+                released
+            }
+            TemporarySkillKind::Spell { skill, duration } => {
+                let pressed = im.is_action_just_pressed(inventory.use_action);
+                if pressed {
+                    let newly_granted = caster.is_some_and(|mut caster| {
+                        let granted = !caster.is_unlocked(skill);
+                        caster.unlock(skill);
+                        granted
+                    });
+                    // a recast refreshes the spell, keeping who has to lock it again
+                    let mut granted = newly_granted;
+                    if let Some(index) = inventory.spells.iter().position(|s| s.name == name) {
+                        granted |= inventory.spells.remove(index).granted;
+                    }
+                    inventory.spells.push(ActiveSpell {
+                        name: name.clone(),
+                        skill: skill.clone(),
+                        remaining: *duration,
+                        granted,
+                    });
+                }
+                pressed
+            }
+        };
+        if !used {
+            continue;
+        }
+        ev_used.send(TemporarySkillUsed {
+            entity,
+            skill: name.clone(),
+        });
+        // a spell using its last charge is still running, expire_spells reports it
+        let running = inventory.spells.iter().any(|s| s.name == name);
+        if inventory.consume(&name) && !running {
+            ev_expired.send(TemporarySkillExpired {
+                entity,
+                skill: name,
+                reason: ExpiryReason::Depleted,
+            });
+        }
+    }
+}
+
+fn expire_spells(
+    time: Res<Time>,
+    mut inventories: Query<(Entity, &mut TemporarySkills, Option<&mut SkillCaster>)>,
+    mut ev_expired: EventWriter<TemporarySkillExpired>,
+) {
+    for (entity, mut inventory, mut caster) in &mut inventories {
+        for spell in inventory.spells.iter_mut() {
+            spell.remaining -= time.delta_secs();
+        }
+        while let Some(index) = inventory.spells.iter().position(|s| s.remaining <= 0.) {
+            let spell = inventory.spells.remove(index);
+            if let (true, Some(caster)) = (spell.granted, caster.as_mut()) {
+                caster.lock(&spell.skill);
+            }
+            let reason = if inventory.charges.contains_key(&spell.name) {
+                ExpiryReason::TimedOut
+            } else {
+                ExpiryReason::Depleted
+            };
+            ev_expired.send(TemporarySkillExpired {
+                entity,
+                skill: spell.name,
+                reason,
+            });
+        }
+    }
+}
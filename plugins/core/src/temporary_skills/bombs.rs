@@ -0,0 +1,256 @@
+use bevy::{
+    picking::mesh_picking::ray_cast::{MeshRayCast, RayCastSettings},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use super::{TemporarySkillBook, TemporarySkillKind, TemporarySkills};
+use crate::{
+    isometric_camera::CameraManager, player_controller::PlayerController, respawn::DespawnOnRespawn,
+};
+
+// seconds between preview arc points
+const PREVIEW_STEP: f32 = 0.05;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BombDef {
+    // seconds from throw to explosion
+    pub fuse: f32,
+    pub radius: f32,
+    pub damage: f32,
+    // camera trauma at the center of the explosion
+    pub shake: f32,
+    pub throw_speed: f32,
+    // degrees above the horizon
+    pub throw_angle: f32,
+    pub gravity: f32,
+}
+
+impl Default for BombDef {
+    fn default() -> Self {
+        Self {
+            fuse: 2.,
+            radius: 2.5,
+            damage: 50.,
+            shake: 0.6,
+            throw_speed: 8.,
+            throw_angle: 35.,
+            gravity: 20.,
+        }
+    }
+}
+
+impl BombDef {
+    /**
+     * Start position and velocity of a throw from the character's hands
+     */
+    fn launch(&self, transform: &Transform, controller: Option<&PlayerController>) -> (Vec3, Vec3) {
+        let forward = controller.map_or(transform.forward().as_vec3(), |c| c.facing(transform));
+        let origin = transform.translation + Vec3::Y + forward * 0.5;
+        let angle = self.throw_angle.to_radians();
+        let velocity = (forward * angle.cos() + Vec3::Y * angle.sin()) * self.throw_speed;
+        (origin, velocity)
+    }
+
+    fn position_at(&self, origin: Vec3, velocity: Vec3, time: f32) -> Vec3 {
+        origin + velocity * time - Vec3::Y * 0.5 * self.gravity * time * time
+    }
+}
+
+#[derive(Resource)]
+pub(super) struct BombAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Bomb {
+    pub owner: Entity,
+    def: BombDef,
+    fuse: f32,
+    velocity: Vec3,
+}
+
+impl Bomb {
+    pub fn fuse_remaining(&self) -> f32 {
+        self.fuse
+    }
+}
+
+/**
+ * World entity destroyed when caught in an explosion, e.g. a cracked wall
+ */
+#[derive(Component)]
+pub struct Explodable;
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Explosion {
+    pub point: Vec3,
+    pub radius: f32,
+    pub damage: f32,
+    // the character that threw the bomb
+    pub source: Entity,
+}
+
+/**
+ * Sent for each Explodable caught in an explosion, right before it is despawned
+ */
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Exploded(pub Entity);
+
+pub(super) fn setup_bomb_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(BombAssets {
+        mesh: meshes.add(Sphere::new(0.2)),
+        material: materials.add(Color::srgb_u8(40, 40, 40)),
+    });
+}
+
+pub(super) fn preview_throws(
+    books: Res<Assets<TemporarySkillBook>>,
+    inventories: Query<(&TemporarySkills, &Transform, Option<&PlayerController>)>,
+    mut gizmos: Gizmos,
+) {
+    for (inventory, transform, controller) in &inventories {
+        if !inventory.aiming {
+            continue;
+        }
+        let Some(TemporarySkillKind::Bomb(def)) = inventory
+            .selected
+            .as_ref()
+            .and_then(|name| books.get(&inventory.book)?.skills.get(name))
+            .map(|skill| &skill.kind)
+        else {
+            continue;
+        };
+
+        let (origin, velocity) = def.launch(transform, controller);
+        let steps = (def.fuse / PREVIEW_STEP).ceil() as usize;
+        let floor = transform.translation.y;
+        let points: Vec<Vec3> = (0..=steps)
+            .map(|i| def.position_at(origin, velocity, i as f32 * PREVIEW_STEP))
+            .take_while(|point| point.y >= floor)
+            .collect();
+        gizmos.linestrip(points.iter().copied(), Color::srgb(1., 0.6, 0.1));
+        if let Some(end) = points.last() {
+            gizmos.circle(
+                Isometry3d::new(
+                    Vec3::new(end.x, floor + 0.01, end.z),
+                    Quat::from_rotation_arc(Vec3::Z, Vec3::Y),
+                ),
+                def.radius,
+                Color::srgb(1., 0.3, 0.1),
+            );
+        }
+    }
+}
+
+pub(super) fn throw_bombs(
+    mut commands: Commands,
+    assets: Res<BombAssets>,
+    mut throwers: Query<(
+        Entity,
+        &mut TemporarySkills,
+        &Transform,
+        Option<&PlayerController>,
+    )>,
+) {
+    for (entity, mut inventory, transform, controller) in &mut throwers {
+        for def in inventory.throws.drain(..) {
+            let (origin, velocity) = def.launch(transform, controller);
+            commands.spawn((
+                Mesh3d(assets.mesh.clone()),
+                MeshMaterial3d(assets.material.clone()),
+                Transform::from_translation(origin),
+                Bomb {
+                    owner: entity,
+                    fuse: def.fuse,
+                    velocity,
+                    def,
+                },
+                // a bomb still flying when its thrower respawns is cleared away
+                DespawnOnRespawn,
+            ));
+        }
+    }
+}
+
+pub(super) fn fly_bombs(
+    time: Res<Time>,
+    mut ray_cast: MeshRayCast,
+    mut bombs: Query<(Entity, &mut Bomb, &mut Transform)>,
+    parents: Query<&Parent>,
+) {
+    let delta = time.delta_secs();
+    for (entity, mut bomb, mut transform) in &mut bombs {
+        bomb.fuse -= delta;
+        if bomb.velocity == Vec3::ZERO {
+            continue;
+        }
+        bomb.velocity.y -= bomb.def.gravity * delta;
+        let step = bomb.velocity * delta;
+        let Ok(direction) = Dir3::new(step) else {
+            continue;
+        };
+
+        // bombs come to rest on whatever they hit first
+        let owner = bomb.owner;
+        let filter = |hit: Entity| {
+            !std::iter::once(hit)
+                .chain(parents.iter_ancestors(hit))
+                .any(|e| e == entity || e == owner)
+        };
+        let ray = Ray3d {
+            origin: transform.translation,
+            direction,
+        };
+        let hit = ray_cast
+            .cast_ray(ray, &RayCastSettings::default().with_filter(&filter))
+            .first()
+            .map(|(_, hit)| (hit.distance, hit.point, hit.normal))
+            .filter(|(distance, _, _)| *distance <= step.length());
+        match hit {
+            Some((_, point, normal)) => {
+                transform.translation = point + normal * 0.2;
+                bomb.velocity = Vec3::ZERO;
+            }
+            None => transform.translation += step,
+        }
+    }
+}
+
+pub(super) fn explode_bombs(
+    mut commands: Commands,
+    mut camera_manager: ResMut<CameraManager>,
+    bombs: Query<(Entity, &Bomb, &Transform)>,
+    explodables: Query<(Entity, &GlobalTransform), With<Explodable>>,
+    mut ev_explosion: EventWriter<Explosion>,
+    mut ev_exploded: EventWriter<Exploded>,
+) {
+    for (entity, bomb, transform) in &bombs {
+        if bomb.fuse > 0. {
+            continue;
+        }
+        let point = transform.translation;
+        let radius = bomb.def.radius;
+        ev_explosion.send(Explosion {
+            point,
+            radius,
+            damage: bomb.def.damage,
+            source: bomb.owner,
+        });
+        camera_manager.add_explosion(point, bomb.def.shake, radius * 4.);
+
+        for (target, global) in &explodables {
+            if global.translation().distance(point) <= radius {
+                ev_exploded.send(Exploded(target));
+                commands.entity(target).despawn_recursive();
+            }
+        }
+        commands.entity(entity).despawn_recursive();
+    }
+}
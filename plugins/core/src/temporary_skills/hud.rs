@@ -0,0 +1,47 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use super::TemporarySkills;
+
+#[derive(Component)]
+pub struct TemporarySkillsHud;
+
+pub(super) fn spawn_hud(mut commands: Commands) {
+    commands.spawn((
+        TemporarySkillsHud,
+        Text::new(""),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..default()
+        },
+    ));
+}
+
+pub(super) fn update_hud(
+    inventories: Query<&TemporarySkills>,
+    mut huds: Query<&mut Text, With<TemporarySkillsHud>>,
+) {
+    let Some(inventory) = inventories.iter().next() else {
+        return;
+    };
+    let mut content = String::new();
+    for (name, charges) in inventory.iter_charges() {
+        let marker = if inventory.selected() == Some(name) {
+            ">"
+        } else {
+            " "
+        };
+        let _ = writeln!(content, "{marker} {name} x{charges}");
+    }
+    for spell in inventory.active_spells() {
+        let _ = writeln!(content, "  {} {:.0}s", spell.name, spell.remaining.ceil());
+    }
+    for mut text in &mut huds {
+        if text.0 != content {
+            text.0.clone_from(&content);
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use std::collections::BTreeSet;
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use crate::{
+    collectibles::{Collector, Wallets},
+    easing::Easing,
+    isometric_camera::rail::{CameraRail, PlayCameraRail},
+    skills::effects::Rotted,
+    temporary_skills::bombs::Explosion,
+};
+
+pub mod persistence;
+
+pub struct TollgatesPlugin;
+
+impl Plugin for TollgatesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<persistence::UnlockedTollgates>()
+            .init_resource::<RottedObstacles>()
+            .add_event::<TollgateUnlocked>()
+            .add_systems(PreStartup, persistence::load_unlocked_tollgates)
+            .add_systems(
+                Update,
+                (
+                    persistence::remove_unlocked,
+                    track_rotted_obstacles,
+                    check_conditions,
+                    unlock_gates,
+                    open_gates,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Last,
+                persistence::save_unlocked_tollgates.after(crate::exit_game::ExitGameSystem),
+            );
+    }
+}
+
+/**
+ * What it takes to get a tollgate open
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub enum UnlockCondition {
+    // an explosion reaching the gate
+    Explosion,
+    // an obstacle tagged with TollgateObstacle(obstacle) rotted away
+    Rot { obstacle: String },
+    // a Collector within reach pays `count` collectibles of `kind`
+    Toll { kind: String, count: u32 },
+}
+
+/**
+ * Gate blocking passage until its condition is met, then it sinks away.
+ * The id is what's remembered in saves, unlocked gates don't spawn again
+ */
+#[derive(Component, Debug, Clone)]
+pub struct Tollgate {
+    pub id: String,
+    pub condition: UnlockCondition,
+    // distance explosions and paying collectors need to come within
+    pub reach: f32,
+    // camera rail played on unlock
+    pub reveal: Option<Handle<CameraRail>>,
+    // seconds before the gate starts opening, leaving the reveal time to frame it
+    pub open_delay: f32,
+    pub open_time: f32,
+}
+
+impl Tollgate {
+    pub fn new(id: &str, condition: UnlockCondition) -> Self {
+        Self {
+            id: id.to_string(),
+            condition,
+            reach: 2.,
+            reveal: None,
+            open_delay: 0.,
+            open_time: 1.,
+        }
+    }
+
+    pub fn with_reach(mut self, reach: f32) -> Self {
+        self.reach = reach;
+        self
+    }
+
+    pub fn with_reveal(mut self, rail: Handle<CameraRail>, open_delay: f32) -> Self {
+        self.reveal = Some(rail);
+        self.open_delay = open_delay;
+        self
+    }
+}
+
+/**
+ * Tags an obstacle, e.g. a Rottable tree, for Rot unlock conditions
+ */
+#[derive(Component, Debug, Clone)]
+pub struct TollgateObstacle(pub String);
+
+#[derive(Event, Debug, Clone)]
+pub struct TollgateUnlocked {
+    pub entity: Entity,
+    pub id: String,
+    // the collector that paid the toll
+    pub paid_by: Option<Entity>,
+}
+
+/**
+ * Tags of the obstacles rotted away so far
+ */
+#[derive(Resource, Debug, Default)]
+pub struct RottedObstacles(BTreeSet<String>);
+
+impl RottedObstacles {
+    pub fn contains(&self, tag: &str) -> bool {
+        self.0.contains(tag)
+    }
+}
+
+#[derive(Component)]
+pub struct Opening {
+    elapsed: f32,
+    delay: f32,
+    duration: f32,
+    from: Option<Vec3>,
+}
+
+/**
+ * Tags are remembered as obstacles appear, Rotted is sent right before
+ * the obstacle is despawned
+ */
+fn track_rotted_obstacles(
+    mut tags: Local<HashMap<Entity, String>>,
+    obstacles: Query<(Entity, &TollgateObstacle), Added<TollgateObstacle>>,
+    mut ev_rotted: EventReader<Rotted>,
+    mut rotted: ResMut<RottedObstacles>,
+) {
+    for (entity, obstacle) in &obstacles {
+        tags.insert(entity, obstacle.0.clone());
+    }
+    for Rotted(entity) in ev_rotted.read() {
+        if let Some(tag) = tags.remove(entity) {
+            rotted.0.insert(tag);
+        }
+    }
+}
+
+fn check_conditions(
+    rotted: Res<RottedObstacles>,
+    mut wallets: ResMut<Wallets>,
+    mut ev_explosion: EventReader<Explosion>,
+    gates: Query<(Entity, &Tollgate, &GlobalTransform), Without<Opening>>,
+    collectors: Query<(Entity, &GlobalTransform), With<Collector>>,
+    mut ev_unlocked: EventWriter<TollgateUnlocked>,
+) {
+    let explosions: Vec<&Explosion> = ev_explosion.read().collect();
+    for (entity, gate, global) in &gates {
+        let position = global.translation();
+        let (unlocked, paid_by) = match &gate.condition {
+            UnlockCondition::Explosion => {
+                let hit = explosions.iter().any(|explosion| {
+                    explosion.point.distance(position) <= explosion.radius + gate.reach
+                });
+                (hit, None)
+            }
+            UnlockCondition::Rot { obstacle } => (rotted.contains(obstacle), None),
+            UnlockCondition::Toll { kind, count } => {
+                let payer = collectors
+                    .iter()
+                    .filter(|(_, collector)| {
+                        collector.translation().distance(position) <= gate.reach
+                    })
+                    .map(|(collector, _)| collector)
+                    .find(|collector| wallets.get_mut(*collector).spend(kind, *count));
+                (payer.is_some(), payer)
+            }
+        };
+        if unlocked {
+            ev_unlocked.send(TollgateUnlocked {
+                entity,
+                id: gate.id.clone(),
+                paid_by,
+            });
+        }
+    }
+}
+
+fn unlock_gates(
+    mut commands: Commands,
+    mut unlocked: ResMut<persistence::UnlockedTollgates>,
+    gates: Query<&Tollgate>,
+    mut ev_unlocked: EventReader<TollgateUnlocked>,
+    mut ev_rail: EventWriter<PlayCameraRail>,
+) {
+    for event in ev_unlocked.read() {
+        let Ok(gate) = gates.get(event.entity) else {
+            continue;
+        };
+        unlocked.insert(&gate.id);
+        if let Some(rail) = &gate.reveal {
+            ev_rail.send(PlayCameraRail(rail.clone()));
+        }
+        commands.entity(event.entity).insert(Opening {
+            elapsed: 0.,
+            delay: gate.open_delay,
+            duration: gate.open_time,
+            from: None,
+        });
+    }
+}
+
+/**
+ * Sinks opening gates into the ground, then despawns them
+ */
+fn open_gates(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut opening: Query<(Entity, &mut Opening, &mut Transform)>,
+) {
+    for (entity, mut state, mut transform) in &mut opening {
+        state.elapsed += time.delta_secs();
+        let elapsed = state.elapsed - state.delay;
+        if elapsed < 0. {
+            continue;
+        }
+        let from = *state.from.get_or_insert(transform.scale);
+        let t = if state.duration > 0. {
+            elapsed / state.duration
+        } else {
+            1.
+        };
+        if t >= 1. {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        transform.scale.y = from.y * (1. - Easing::QuadraticIn.sample(t));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<RottedObstacles>();
+        world.init_resource::<Wallets>();
+        world.init_resource::<Events<Explosion>>();
+        world.init_resource::<Events<TollgateUnlocked>>();
+        world
+    }
+
+    fn toll_gate(world: &mut World, count: u32) -> Entity {
+        let condition = UnlockCondition::Toll {
+            kind: "shroom".to_string(),
+            count,
+        };
+        world
+            .spawn((Tollgate::new("gate", condition), GlobalTransform::IDENTITY))
+            .id()
+    }
+
+    fn collector(world: &mut World, x: f32, shrooms: u32) -> Entity {
+        let entity = world
+            .spawn((Collector, GlobalTransform::from_xyz(x, 0., 0.)))
+            .id();
+        world
+            .resource_mut::<Wallets>()
+            .get_mut(entity)
+            .add("shroom", shrooms);
+        entity
+    }
+
+    fn unlocked(world: &mut World) -> Vec<TollgateUnlocked> {
+        world
+            .resource_mut::<Events<TollgateUnlocked>>()
+            .drain()
+            .collect()
+    }
+
+    #[test]
+    fn toll_is_paid_by_a_collector_in_reach() {
+        let mut world = world();
+        let gate = toll_gate(&mut world, 3);
+        let payer = collector(&mut world, 1., 5);
+
+        world.run_system_once(check_conditions).unwrap();
+        let events = unlocked(&mut world);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].entity, gate);
+        assert_eq!(events[0].paid_by, Some(payer));
+        assert_eq!(world.resource::<Wallets>().count(payer, "shroom"), 2);
+    }
+
+    #[test]
+    fn toll_needs_the_full_amount() {
+        let mut world = world();
+        toll_gate(&mut world, 3);
+        let poor = collector(&mut world, 1., 2);
+
+        world.run_system_once(check_conditions).unwrap();
+        assert!(unlocked(&mut world).is_empty());
+        assert_eq!(world.resource::<Wallets>().count(poor, "shroom"), 2);
+    }
+
+    #[test]
+    fn toll_is_only_paid_within_reach() {
+        let mut world = world();
+        toll_gate(&mut world, 3);
+        let far = collector(&mut world, 5., 10);
+
+        world.run_system_once(check_conditions).unwrap();
+        assert!(unlocked(&mut world).is_empty());
+        assert_eq!(world.resource::<Wallets>().count(far, "shroom"), 10);
+    }
+
+    #[test]
+    fn toll_is_paid_by_whoever_can_afford_it() {
+        let mut world = world();
+        toll_gate(&mut world, 3);
+        let poor = collector(&mut world, 1., 2);
+        let rich = collector(&mut world, -1., 4);
+
+        world.run_system_once(check_conditions).unwrap();
+        let events = unlocked(&mut world);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].paid_by, Some(rich));
+        let wallets = world.resource::<Wallets>();
+        assert_eq!(wallets.count(poor, "shroom"), 2);
+        assert_eq!(wallets.count(rich, "shroom"), 1);
+    }
+
+    #[test]
+    fn explosions_and_rot_unlock_their_gates() {
+        let mut world = world();
+        world.spawn((
+            Tollgate::new("blast", UnlockCondition::Explosion),
+            GlobalTransform::from_xyz(0., 0., 0.),
+        ));
+        world.spawn((
+            Tollgate::new(
+                "overgrown",
+                UnlockCondition::Rot {
+                    obstacle: "thorns".to_string(),
+                },
+            ),
+            GlobalTransform::from_xyz(50., 0., 0.),
+        ));
+        world.send_event(Explosion {
+            point: Vec3::new(3., 0., 0.),
+            radius: 1.5,
+            damage: 10.,
+            source: Entity::PLACEHOLDER,
+        });
+        world
+            .resource_mut::<RottedObstacles>()
+            .0
+            .insert("thorns".to_string());
+
+        world.run_system_once(check_conditions).unwrap();
+        let mut ids: Vec<String> = unlocked(&mut world).into_iter().map(|e| e.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["blast".to_string(), "overgrown".to_string()]);
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::BTreeSet;
+
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use super::{Tollgate, TollgateUnlocked};
+use crate::ron_asset::{load_ron_file, save_ron_file};
+
+pub const UNLOCKED_TOLLGATES_PATH: &str = "unlocked_tollgates.ron";
+
+/**
+ * Ids of the tollgates unlocked so far, kept across sessions
+ */
+#[derive(Resource, Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnlockedTollgates {
+    pub ids: BTreeSet<String>,
+}
+
+impl UnlockedTollgates {
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+
+    pub fn insert(&mut self, id: &str) {
+        self.ids.insert(id.to_string());
+    }
+}
+
+pub(super) fn load_unlocked_tollgates(mut unlocked: ResMut<UnlockedTollgates>) {
+    if let Some(loaded) = load_ron_file::<UnlockedTollgates>(UNLOCKED_TOLLGATES_PATH) {
+        *unlocked = loaded;
+    }
+}
+
+/**
+ * Gates unlocked in an earlier session are despawned as they appear
+ */
+pub(super) fn remove_unlocked(
+    mut commands: Commands,
+    unlocked: Res<UnlockedTollgates>,
+    gates: Query<(Entity, &Tollgate), Added<Tollgate>>,
+) {
+    for (entity, gate) in &gates {
+        if unlocked.contains(&gate.id) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/**
+ * Written as soon as a gate unlocks, and on exit
+ */
+pub(super) fn save_unlocked_tollgates(
+    ev_exit: EventReader<AppExit>,
+    mut ev_unlocked: EventReader<TollgateUnlocked>,
+    unlocked: Res<UnlockedTollgates>,
+) {
+    // counted rather than is_empty(), so the events are marked as read
+    let gate_unlocked = ev_unlocked.read().count() > 0;
+    if !gate_unlocked && ev_exit.is_empty() {
+        return;
+    }
+    save_ron_file(UNLOCKED_TOLLGATES_PATH, &*unlocked);
+}
@@ -0,0 +1,22 @@
+use bevy::prelude::*;
+
+/**
+ * Axis-aligned box centered on its entity, shared by trigger-like components
+ * such as updrafts, checkpoints and hazards. The entity's rotation and scale
+ * don't affect it
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoxVolume {
+    pub half_extents: Vec3,
+}
+
+impl BoxVolume {
+    pub fn new(half_extents: Vec3) -> Self {
+        Self { half_extents }
+    }
+
+    pub fn contains(&self, transform: &GlobalTransform, point: Vec3) -> bool {
+        let local = (point - transform.translation()).abs();
+        local.cmple(self.half_extents).all()
+    }
+}